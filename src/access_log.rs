@@ -0,0 +1,224 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::Invocation;
+use lambda_http::request::RequestContext;
+use lambda_http::{Request, RequestExt};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Characters that would end the request line of a Common Log Format line.
+const REQUEST_LINE: &AsciiSet = &CONTROLS.add(b' ').add(b'"');
+
+/// Format of the access log lines, once `AccessLog::On` is resolved.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Format {
+    Json,
+    Common,
+}
+
+/// Returns the type of event that triggered the invocation.
+pub(crate) fn event_source(event: &Request) -> &'static str {
+    match event.request_context_ref() {
+        Some(RequestContext::ApiGatewayV1(_)) => "apigw_rest",
+        // Function URLs send API Gateway v2 payloads, from a lambda-url domain
+        Some(RequestContext::ApiGatewayV2(context))
+            if context
+                .domain_name
+                .as_deref()
+                .is_some_and(|domain| domain.contains(".lambda-url.")) =>
+        {
+            "function_url"
+        }
+        Some(RequestContext::ApiGatewayV2(_)) => "apigw_http",
+        Some(RequestContext::Alb(_)) => "alb",
+        Some(RequestContext::PassThrough) => "pass_through",
+        _ => "unknown",
+    }
+}
+
+/// Returns the HTTP version of the client's request, e.g. `HTTP/1.1`, if the event has it.
+pub(crate) fn protocol(event: &Request) -> Option<String> {
+    match event.request_context_ref() {
+        Some(RequestContext::ApiGatewayV1(context)) => context.protocol.clone(),
+        Some(RequestContext::ApiGatewayV2(context)) => context.http.protocol.clone(),
+        _ => None,
+    }
+}
+
+/// Formats the access log line of an invocation.
+pub(crate) fn line(invocation: &Invocation, format: Format, time: SystemTime) -> String {
+    let latency_ms = invocation.upstream_latency.as_secs_f64() * 1000.0;
+    match format {
+        Format::Json => json!({
+            "time": rfc3339(time),
+            "type": "access",
+            "requestId": invocation.request_id,
+            "method": invocation.method,
+            "path": invocation.path,
+            "status": invocation.status,
+            "upstreamLatencyMs": latency_ms,
+            "responseBytes": invocation.response_bytes,
+            "source": invocation.source,
+            "coldStart": invocation.cold_start,
+        })
+        .to_string(),
+        Format::Common => format!(
+            "- - - [{}] \"{} {} {}\" {} {} request_id={} source={} latency_ms={:.3} cold_start={}",
+            common_log_time(time),
+            invocation.method,
+            utf8_percent_encode(&invocation.path, REQUEST_LINE),
+            invocation.protocol.as_deref().unwrap_or("-"),
+            invocation.status.map_or("-".to_string(), |status| status.to_string()),
+            invocation.response_bytes,
+            invocation.request_id,
+            invocation.source,
+            latency_ms,
+            invocation.cold_start,
+        ),
+    }
+}
+
+/// Formats a UTC time as `2000-10-10T13:55:36.000Z`.
+fn rfc3339(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second, millis) = utc(time);
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{millis:03}Z")
+}
+
+/// Formats a UTC time as `10/Oct/2000:13:55:36 +0000`.
+fn common_log_time(time: SystemTime) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (year, month, day, hour, minute, second, _) = utc(time);
+    format!(
+        "{day:02}/{}/{year:04}:{hour:02}:{minute:02}:{second:02} +0000",
+        MONTHS[month as usize - 1]
+    )
+}
+
+/// Splits a time into UTC year, month, day, hour, minute, second and millisecond.
+fn utc(time: SystemTime) -> (i64, u32, u32, u64, u64, u64, u32) {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (hour, minute, second) = (secs / 3600 % 24, secs / 60 % 60, secs % 60);
+
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, hour, minute, second, since_epoch.subsec_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn invocation() -> Invocation {
+        let mut invocation = Invocation::new(&Request::default(), true, None, None);
+        invocation.request_id = "req-1".to_string();
+        invocation.method = "GET".to_string();
+        invocation.path = "/pets/42".to_string();
+        invocation.protocol = Some("HTTP/1.1".to_string());
+        invocation.source = "apigw_http";
+        invocation.upstream_latency = Duration::from_micros(12_500);
+        invocation.status = Some(200);
+        invocation.response_bytes = 42;
+        invocation
+    }
+
+    #[test]
+    fn test_json_line() {
+        let time = UNIX_EPOCH + Duration::from_millis(971_186_136_123);
+        let line: serde_json::Value = serde_json::from_str(&line(&invocation(), Format::Json, time)).unwrap();
+
+        assert_eq!(
+            line,
+            json!({
+                "time": "2000-10-10T13:55:36.123Z",
+                "type": "access",
+                "requestId": "req-1",
+                "method": "GET",
+                "path": "/pets/42",
+                "status": 200,
+                "upstreamLatencyMs": 12.5,
+                "responseBytes": 42,
+                "source": "apigw_http",
+                "coldStart": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_common_line() {
+        let time = UNIX_EPOCH + Duration::from_secs(971_186_136);
+        let mut invocation = invocation();
+        assert_eq!(
+            line(&invocation, Format::Common, time),
+            "- - - [10/Oct/2000:13:55:36 +0000] \"GET /pets/42 HTTP/1.1\" 200 42 request_id=req-1 source=apigw_http latency_ms=12.500 cold_start=true"
+        );
+
+        invocation.status = None;
+        assert!(line(&invocation, Format::Common, time).contains("\" - 42 "));
+
+        // the request line stays one quoted field, and ALB events don't have the protocol
+        invocation.path = "/a b/\"c\"".to_string();
+        invocation.protocol = None;
+        assert!(line(&invocation, Format::Common, time).contains(" \"GET /a%20b/%22c%22 -\" "));
+    }
+
+    #[test]
+    fn test_event_source() {
+        let http_api = |domain_name: &str| {
+            format!(
+                r#"{{
+                    "version": "2.0",
+                    "routeKey": "$default",
+                    "rawPath": "/",
+                    "rawQueryString": "",
+                    "headers": {{}},
+                    "requestContext": {{
+                        "accountId": "123456789012",
+                        "apiId": "abc",
+                        "domainName": "{domain_name}",
+                        "http": {{ "method": "GET", "path": "/", "protocol": "HTTP/1.1", "sourceIp": "203.0.113.1", "userAgent": "test" }},
+                        "requestId": "id",
+                        "routeKey": "$default",
+                        "stage": "$default",
+                        "time": "12/Mar/2020:19:03:58 +0000",
+                        "timeEpoch": 1583348638390
+                    }},
+                    "isBase64Encoded": false
+                }}"#
+            )
+        };
+
+        let function_url = lambda_http::request::from_str(&http_api("abc.lambda-url.us-east-1.on.aws")).unwrap();
+        assert_eq!(event_source(&function_url), "function_url");
+        let api = lambda_http::request::from_str(&http_api("abc.execute-api.us-east-1.amazonaws.com")).unwrap();
+        assert_eq!(event_source(&api), "apigw_http");
+        assert_eq!(event_source(&Request::default()), "unknown");
+
+        assert_eq!(protocol(&api).as_deref(), Some("HTTP/1.1"));
+        assert_eq!(protocol(&Request::default()), None);
+    }
+
+    #[test]
+    fn test_utc() {
+        assert_eq!(utc(UNIX_EPOCH), (1970, 1, 1, 0, 0, 0, 0));
+        // leap day
+        assert_eq!(
+            utc(UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            (2024, 2, 29, 12, 34, 56, 0)
+        );
+    }
+}
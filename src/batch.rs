@@ -0,0 +1,713 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::events::{content_type, insert_header as insert, is_dot_segment};
+use crate::EventSource;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use http::{HeaderMap, HeaderValue, Method, Request, StatusCode};
+use http_body_util::BodyExt;
+use hyper_util::client::legacy::connect::Connect;
+use hyper_util::client::legacy::Client;
+use lambda_http::{Body, Error};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use serde_json::{json, Value};
+use tokio::task::JoinSet;
+use tokio::time::{timeout_at, Instant};
+use tracing::Instrument;
+use url::Url;
+
+/// Characters encoded in a path segment.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// A record of a batch event, forwarded to the app as its own request.
+#[derive(Debug, PartialEq)]
+pub(crate) struct BatchItem {
+    /// Identifier reported in `batchItemFailures` if the app fails to process the record.
+    pub(crate) id: String,
+    /// Headers describing the record, added to the request.
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Vec<u8>,
+    /// Method and path of the request, instead of a `POST` to the pass-through path.
+    pub(crate) route: Option<(Method, String)>,
+    /// Whether the record is reported as failed without being sent, because its route
+    /// can't be built safely.
+    pub(crate) rejected: bool,
+}
+
+/// The records of a batch event.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Batch {
+    pub(crate) items: Vec<BatchItem>,
+    /// Whether the records must be processed one at a time, in order. Once a record
+    /// fails, the following ones are not sent.
+    pub(crate) ordered: bool,
+    pub(crate) report: Report,
+}
+
+/// How the outcome of a batch is returned to Lambda.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Report {
+    /// `batchItemFailures` listing every record that failed or was not sent.
+    Failures,
+    /// `batchItemFailures` listing only the first failed record, for streams: Lambda
+    /// checkpoints before the lowest failed sequence number, and retries from there.
+    FirstFailure,
+    /// The status code of each record, as an error if any record failed, so Lambda
+    /// retries the invocation.
+    Results,
+}
+
+/// Method and path template of the requests S3 objects are sent as, e.g.
+/// `POST /s3/{bucket}/{key}`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ObjectRoute {
+    method: Method,
+    path: String,
+}
+
+impl ObjectRoute {
+    /// Parses a `METHOD /path` route, falling back to `POST /s3/{bucket}/{key}` if it is invalid.
+    pub(crate) fn new(input: &str) -> Self {
+        let route = input.trim().split_once(' ').and_then(|(method, path)| {
+            let method = Method::from_bytes(method.to_uppercase().as_bytes()).ok()?;
+            let path = path.trim();
+            path.starts_with('/').then(|| ObjectRoute {
+                method,
+                path: path.to_string(),
+            })
+        });
+        route.unwrap_or_else(|| {
+            tracing::warn!("Invalid S3 object route: {}", input);
+            ObjectRoute::default()
+        })
+    }
+
+    /// The method and path of an object, with `{bucket}` and `{key}` replaced. The key
+    /// keeps its `/` separators, other reserved characters are percent-encoded.
+    ///
+    /// Returns `None` if the bucket or a key segment is `.` or `..`: URLs normalise dot
+    /// segments, even percent-encoded ones, so the path could escape the route.
+    fn resolve(&self, bucket: &str, key: &str) -> Option<(Method, String)> {
+        if is_dot_segment(bucket) || key.split('/').any(is_dot_segment) {
+            return None;
+        }
+        let bucket = utf8_percent_encode(bucket, SEGMENT).to_string();
+        let key = key
+            .split('/')
+            .map(|segment| utf8_percent_encode(segment, SEGMENT).to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        let path = self.path.replace("{bucket}", &bucket).replace("{key}", &key);
+        Some((self.method.clone(), path))
+    }
+}
+
+impl Default for ObjectRoute {
+    fn default() -> Self {
+        ObjectRoute {
+            method: Method::POST,
+            path: "/s3/{bucket}/{key}".to_string(),
+        }
+    }
+}
+
+impl Batch {
+    /// Event sources whose events can be fanned out.
+    pub(crate) const SOURCES: [EventSource; 4] = [
+        EventSource::Sqs,
+        EventSource::Kinesis,
+        EventSource::DynamoDb,
+        EventSource::S3,
+    ];
+
+    /// Splits an event into its records, if events of `source` can be fanned out.
+    ///
+    /// Returns `None` if a record has no ID to report it as failed with: skipping it would
+    /// have Lambda delete it, so the event is forwarded as is instead.
+    pub(crate) fn new(source: EventSource, event: &Value, object_route: &ObjectRoute) -> Option<Self> {
+        match source {
+            EventSource::Sqs => sqs_batch(event),
+            EventSource::Kinesis => kinesis_batch(event),
+            EventSource::DynamoDb => dynamodb_batch(event),
+            EventSource::S3 => s3_batch(event, object_route),
+            _ => None,
+        }
+    }
+}
+
+fn records(event: &Value) -> &[Value] {
+    event["Records"].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+fn sqs_batch(event: &Value) -> Option<Batch> {
+    let records = records(event);
+    let items = records
+        .iter()
+        .map(|record| {
+            let Some(id) = record["messageId"].as_str() else {
+                tracing::warn!("SQS record without messageId, forwarding the event as is");
+                return None;
+            };
+            let attributes = &record["attributes"];
+            let mut headers = HeaderMap::new();
+            insert(&mut headers, "x-amzn-sqs-message-id", Some(id));
+            insert(
+                &mut headers,
+                "x-amzn-sqs-receive-count",
+                attributes["ApproximateReceiveCount"].as_str(),
+            );
+            insert(
+                &mut headers,
+                "x-amzn-sqs-message-group-id",
+                attributes["MessageGroupId"].as_str(),
+            );
+            Some(BatchItem::new(
+                id.to_string(),
+                headers,
+                record["body"].as_str().unwrap_or_default().as_bytes().to_vec(),
+            ))
+        })
+        .collect::<Option<_>>()?;
+    // FIFO queues deliver the messages of a group in order
+    let ordered = records.iter().any(|record| {
+        record["eventSourceARN"]
+            .as_str()
+            .is_some_and(|arn| arn.ends_with(".fifo"))
+    });
+    Some(Batch {
+        items,
+        ordered,
+        report: Report::Failures,
+    })
+}
+
+fn kinesis_batch(event: &Value) -> Option<Batch> {
+    let items = records(event)
+        .iter()
+        .map(|record| {
+            let kinesis = &record["kinesis"];
+            let Some(sequence_number) = kinesis["sequenceNumber"].as_str() else {
+                tracing::warn!("Kinesis record without sequenceNumber, forwarding the event as is");
+                return None;
+            };
+            let mut headers = HeaderMap::new();
+            insert(&mut headers, "x-amzn-kinesis-sequence-number", Some(sequence_number));
+            insert(
+                &mut headers,
+                "x-amzn-kinesis-partition-key",
+                kinesis["partitionKey"].as_str(),
+            );
+            let data = kinesis["data"].as_str().unwrap_or_default();
+            let body = STANDARD.decode(data).unwrap_or_else(|e| {
+                tracing::warn!(sequence_number, error = %e, "Kinesis record data is not valid base64");
+                data.as_bytes().to_vec()
+            });
+            Some(BatchItem::new(sequence_number.to_string(), headers, body))
+        })
+        .collect::<Option<_>>()?;
+    Some(Batch {
+        items,
+        ordered: true,
+        report: Report::FirstFailure,
+    })
+}
+
+fn dynamodb_batch(event: &Value) -> Option<Batch> {
+    let items = records(event)
+        .iter()
+        .map(|record| {
+            let dynamodb = &record["dynamodb"];
+            let Some(sequence_number) = dynamodb["SequenceNumber"].as_str() else {
+                tracing::warn!("DynamoDB record without SequenceNumber, forwarding the event as is");
+                return None;
+            };
+            let mut headers = HeaderMap::new();
+            insert(&mut headers, "x-amzn-dynamodb-sequence-number", Some(sequence_number));
+            insert(&mut headers, "x-amzn-dynamodb-event-name", record["eventName"].as_str());
+            // the new image, the old one for REMOVE events, or the keys, depending on the stream view type
+            let image = ["NewImage", "OldImage", "Keys"]
+                .into_iter()
+                .find_map(|name| dynamodb.get(name))
+                .map(unmarshall)
+                .unwrap_or(Value::Null);
+            Some(BatchItem::new(
+                sequence_number.to_string(),
+                headers,
+                image.to_string().into_bytes(),
+            ))
+        })
+        .collect::<Option<_>>()?;
+    Some(Batch {
+        items,
+        ordered: true,
+        report: Report::FirstFailure,
+    })
+}
+
+fn s3_batch(event: &Value, object_route: &ObjectRoute) -> Option<Batch> {
+    let items = records(event)
+        .iter()
+        .map(|record| {
+            let s3 = &record["s3"];
+            let object = &s3["object"];
+            let (Some(bucket), Some(key)) = (s3["bucket"]["name"].as_str(), object["key"].as_str()) else {
+                tracing::warn!("S3 record without bucket name or object key, forwarding the event as is");
+                return None;
+            };
+            // keys are URL-encoded in S3 events, with spaces as '+'
+            let key = percent_decode_str(&key.replace('+', " "))
+                .decode_utf8_lossy()
+                .into_owned();
+            let mut headers = HeaderMap::new();
+            insert(&mut headers, "x-amzn-s3-event-name", record["eventName"].as_str());
+            insert(&mut headers, "x-amzn-s3-bucket", Some(bucket));
+            insert(&mut headers, "x-amzn-s3-key", Some(&key));
+            insert(
+                &mut headers,
+                "x-amzn-s3-object-size",
+                object["size"].as_u64().map(|size| size.to_string()).as_deref(),
+            );
+            insert(&mut headers, "x-amzn-s3-object-etag", object["eTag"].as_str());
+            insert(
+                &mut headers,
+                "x-amzn-s3-object-version-id",
+                object["versionId"].as_str(),
+            );
+            let mut item = BatchItem::new(format!("s3://{bucket}/{key}"), headers, record.to_string().into_bytes());
+            item.route = object_route.resolve(bucket, &key);
+            if item.route.is_none() {
+                tracing::warn!(
+                    item_id = item.id,
+                    "S3 object key has a '.' or '..' segment, reporting it as failed"
+                );
+                item.rejected = true;
+            }
+            Some(item)
+        })
+        .collect::<Option<_>>()?;
+    Some(Batch {
+        items,
+        ordered: false,
+        report: Report::Results,
+    })
+}
+
+/// Converts a DynamoDB item in attribute value format, e.g. `{"id": {"N": "1"}}`,
+/// to plain JSON, e.g. `{"id": "1"}`. Binary values stay base64-encoded strings.
+///
+/// Numbers stay strings: they have up to 38 digits, which a JSON parser could round, and
+/// a field has the same type in every record.
+fn unmarshall(item: &Value) -> Value {
+    let attributes = item.as_object().into_iter().flatten();
+    Value::Object(
+        attributes
+            .map(|(name, value)| (name.clone(), attribute_value(value)))
+            .collect(),
+    )
+}
+
+fn attribute_value(value: &Value) -> Value {
+    let Some((kind, value)) = value.as_object().and_then(|value| value.iter().next()) else {
+        return Value::Null;
+    };
+    match kind.as_str() {
+        "L" => Value::Array(value.as_array().into_iter().flatten().map(attribute_value).collect()),
+        "M" => unmarshall(value),
+        "NULL" => Value::Null,
+        // S, N, B, BOOL, SS, NS and BS are plain JSON already
+        _ => value.clone(),
+    }
+}
+
+impl BatchItem {
+    fn new(id: String, mut headers: HeaderMap, body: Vec<u8>) -> Self {
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static(content_type(&body)),
+        );
+        BatchItem {
+            id,
+            headers,
+            body,
+            route: None,
+            rejected: false,
+        }
+    }
+}
+
+/// Sends each record of `batch` to the app as a `POST` to `url`, or its own route, with
+/// `headers` and the record's own headers, and returns the response to Lambda as set by
+/// [`Batch::report`]. Records the app answered with a non-2xx status, or didn't answer
+/// before `deadline`, have failed.
+pub(crate) async fn send_batch<C>(
+    client: &Client<C, Body>,
+    url: &Url,
+    headers: &HeaderMap,
+    batch: Batch,
+    concurrency: usize,
+    deadline: Option<Instant>,
+) -> Result<Value, Error>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    let mut statuses: Vec<Option<StatusCode>> = vec![None; batch.items.len()];
+    let success = |status: Option<StatusCode>| status.is_some_and(|status| status.is_success());
+
+    if batch.ordered || concurrency <= 1 {
+        for (index, item) in batch.items.iter().enumerate() {
+            if !item.rejected {
+                statuses[index] = send(client, request(url, headers, item), &item.id, deadline).await;
+            }
+            if batch.ordered && !success(statuses[index]) {
+                break;
+            }
+        }
+    } else {
+        let mut tasks = JoinSet::new();
+        for (index, item) in batch.items.iter().enumerate().filter(|(_, item)| !item.rejected) {
+            if tasks.len() >= concurrency {
+                if let Some(Ok((index, status))) = tasks.join_next().await {
+                    statuses[index] = status;
+                }
+            }
+            let client = client.clone();
+            let request = request(url, headers, item);
+            let id = item.id.clone();
+            tasks.spawn(async move { (index, send(&client, request, &id, deadline).await) }.in_current_span());
+        }
+        while let Some(result) = tasks.join_next().await {
+            if let Ok((index, status)) = result {
+                statuses[index] = status;
+            }
+        }
+    }
+
+    let failed = statuses.iter().filter(|status| !success(**status)).count();
+    if failed > 0 {
+        tracing::info!(records = batch.items.len(), failed, "batch records failed");
+    }
+
+    if batch.report == Report::Results {
+        let results: Vec<Value> = batch
+            .items
+            .iter()
+            .zip(&statuses)
+            .map(|(item, status)| json!({ "itemIdentifier": item.id, "statusCode": status.map(|status| status.as_u16()) }))
+            .collect();
+        let results = json!({ "results": results });
+        return if failed > 0 {
+            Err(Error::from(results.to_string()))
+        } else {
+            Ok(results)
+        };
+    }
+
+    let failures: Vec<Value> = batch
+        .items
+        .iter()
+        .zip(statuses)
+        .filter(|(_, status)| !success(*status))
+        .take(if batch.report == Report::FirstFailure {
+            1
+        } else {
+            usize::MAX
+        })
+        .map(|(item, _)| json!({ "itemIdentifier": item.id }))
+        .collect();
+    Ok(json!({ "batchItemFailures": failures }))
+}
+
+fn request(url: &Url, headers: &HeaderMap, item: &BatchItem) -> Request<Body> {
+    let mut request = Request::new(Body::Binary(item.body.clone()));
+    let mut url = url.clone();
+    *request.method_mut() = match &item.route {
+        Some((method, path)) => {
+            url.set_path(path);
+            method.clone()
+        }
+        None => Method::POST,
+    };
+    *request.uri_mut() = url.as_str().parse().unwrap_or_default();
+    let request_headers = request.headers_mut();
+    request_headers.extend(headers.clone());
+    request_headers.remove(http::header::CONTENT_LENGTH);
+    request_headers.extend(item.headers.clone());
+    request
+}
+
+/// Sends one record, and returns the status code of the response, if any.
+async fn send<C>(
+    client: &Client<C, Body>,
+    #[cfg_attr(not(feature = "otel"), allow(unused_mut))] mut request: Request<Body>,
+    id: &str,
+    deadline: Option<Instant>,
+) -> Option<StatusCode>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    let span = tracing::info_span!(
+        "upstream_request",
+        otel.kind = "client",
+        http.request.method = %request.method(),
+        url.path = request.uri().path(),
+        batch.item_id = id,
+        http.response.status_code = tracing::field::Empty,
+    );
+    // continue the Lambda trace from the upstream_request span instead
+    #[cfg(feature = "otel")]
+    if request.headers().contains_key("traceparent") {
+        crate::otel::inject_traceparent(&span, request.headers_mut());
+    }
+    let exchange = async {
+        let response = client.request(request).await?;
+        let status = response.status();
+        // read the body, so the connection can be reused
+        response.into_body().collect().await?;
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(status)
+    }
+    .instrument(span.clone());
+    let result = match deadline {
+        Some(deadline) => timeout_at(deadline, exchange)
+            .await
+            .unwrap_or_else(|_| Err("timed out before the invocation deadline".into())),
+        None => exchange.await,
+    };
+    match result {
+        Ok(status) => {
+            span.record("http.response.status_code", status.as_u16());
+            if !status.is_success() {
+                tracing::warn!(
+                    item_id = id,
+                    status = status.as_u16(),
+                    "app failed to process batch record"
+                );
+            }
+            Some(status)
+        }
+        Err(e) => {
+            tracing::warn!(item_id = id, error = %e, "failed to send batch record");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqs_batch() {
+        let event = json!({
+            "Records": [
+                {
+                    "messageId": "1",
+                    "body": "{\"order\":42}",
+                    "attributes": { "ApproximateReceiveCount": "2" },
+                    "eventSource": "aws:sqs",
+                    "eventSourceARN": "arn:aws:sqs:us-east-1:123456789012:orders"
+                },
+                { "messageId": "2", "body": "hello", "eventSource": "aws:sqs" }
+            ]
+        });
+        let batch = Batch::new(EventSource::Sqs, &event, &ObjectRoute::default()).unwrap();
+
+        assert!(!batch.ordered);
+        assert_eq!(batch.items.len(), 2);
+        assert_eq!(batch.items[0].id, "1");
+        assert_eq!(batch.items[0].body, b"{\"order\":42}");
+        assert_eq!(batch.items[0].headers["x-amzn-sqs-message-id"], "1");
+        assert_eq!(batch.items[0].headers["x-amzn-sqs-receive-count"], "2");
+        assert_eq!(batch.items[0].headers["content-type"], "application/json");
+        assert_eq!(batch.items[1].headers["content-type"], "text/plain; charset=utf-8");
+        assert!(!batch.items[1].headers.contains_key("x-amzn-sqs-receive-count"));
+
+        // a record that can't be reported as failed is not dropped, the event is forwarded as is
+        let mut event = event;
+        event["Records"][1]["messageId"].take();
+        assert_eq!(Batch::new(EventSource::Sqs, &event, &ObjectRoute::default()), None);
+    }
+
+    #[test]
+    fn test_sqs_fifo_batch() {
+        let event = json!({
+            "Records": [{
+                "messageId": "1",
+                "body": "hello",
+                "attributes": { "MessageGroupId": "group-1" },
+                "eventSource": "aws:sqs",
+                "eventSourceARN": "arn:aws:sqs:us-east-1:123456789012:orders.fifo"
+            }]
+        });
+        let batch = Batch::new(EventSource::Sqs, &event, &ObjectRoute::default()).unwrap();
+
+        assert!(batch.ordered);
+        assert_eq!(batch.items[0].headers["x-amzn-sqs-message-group-id"], "group-1");
+        assert_eq!(
+            Batch::new(EventSource::EventBridge, &event, &ObjectRoute::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_kinesis_batch() {
+        let event = json!({
+            "Records": [
+                {
+                    "kinesis": {
+                        "partitionKey": "order-42",
+                        "sequenceNumber": "49590338271490256608559692538361571095921575989136588898",
+                        "data": "eyJvcmRlciI6NDJ9"
+                    },
+                    "eventSource": "aws:kinesis"
+                },
+                { "kinesis": { "sequenceNumber": "2", "data": "/w==" }, "eventSource": "aws:kinesis" }
+            ]
+        });
+        let batch = Batch::new(EventSource::Kinesis, &event, &ObjectRoute::default()).unwrap();
+
+        assert!(batch.ordered);
+        assert_eq!(batch.report, Report::FirstFailure);
+        assert_eq!(batch.items[0].body, b"{\"order\":42}");
+        assert_eq!(
+            batch.items[0].headers["x-amzn-kinesis-sequence-number"],
+            "49590338271490256608559692538361571095921575989136588898"
+        );
+        assert_eq!(batch.items[0].headers["x-amzn-kinesis-partition-key"], "order-42");
+        assert_eq!(batch.items[0].headers["content-type"], "application/json");
+        assert_eq!(batch.items[1].body, [0xff]);
+        assert_eq!(batch.items[1].headers["content-type"], "application/octet-stream");
+    }
+
+    #[test]
+    fn test_dynamodb_batch() {
+        let event = json!({
+            "Records": [
+                {
+                    "eventName": "INSERT",
+                    "dynamodb": {
+                        "Keys": { "id": { "N": "1" } },
+                        "NewImage": {
+                            "id": { "N": "1" },
+                            "name": { "S": "Jane" },
+                            "active": { "BOOL": true },
+                            "tags": { "SS": ["a", "b"] },
+                            "scores": { "L": [{ "N": "1.5" }, { "NULL": true }] },
+                            "counts": { "NS": ["-7", "18446744073709551615", "12345678901234567890123"] },
+                            "address": { "M": { "city": { "S": "Seattle" } } }
+                        },
+                        "SequenceNumber": "111"
+                    },
+                    "eventSource": "aws:dynamodb"
+                },
+                {
+                    "eventName": "REMOVE",
+                    "dynamodb": { "Keys": { "id": { "N": "2" } }, "SequenceNumber": "222" },
+                    "eventSource": "aws:dynamodb"
+                }
+            ]
+        });
+        let batch = Batch::new(EventSource::DynamoDb, &event, &ObjectRoute::default()).unwrap();
+
+        assert!(batch.ordered);
+        assert_eq!(batch.report, Report::FirstFailure);
+        assert_eq!(batch.items[0].id, "111");
+        assert_eq!(batch.items[0].headers["x-amzn-dynamodb-event-name"], "INSERT");
+        assert_eq!(
+            serde_json::from_slice::<Value>(&batch.items[0].body).unwrap(),
+            json!({
+                "id": "1",
+                "name": "Jane",
+                "active": true,
+                "tags": ["a", "b"],
+                "scores": ["1.5", null],
+                "counts": ["-7", "18446744073709551615", "12345678901234567890123"],
+                "address": { "city": "Seattle" }
+            })
+        );
+        assert_eq!(batch.items[1].headers["x-amzn-dynamodb-event-name"], "REMOVE");
+        assert_eq!(batch.items[1].body, b"{\"id\":\"2\"}");
+    }
+
+    #[test]
+    fn test_s3_batch() {
+        let event = json!({
+            "Records": [{
+                "eventSource": "aws:s3",
+                "eventName": "ObjectCreated:Put",
+                "s3": {
+                    "bucket": { "name": "uploads" },
+                    "object": {
+                        "key": "photos/summer+2024/caf%C3%A9%3F.jpg",
+                        "size": 1024,
+                        "eTag": "d41d8cd98f00b204e9800998ecf8427e"
+                    }
+                }
+            }]
+        });
+        let batch = Batch::new(
+            EventSource::S3,
+            &event,
+            &ObjectRoute::new("put /objects/{bucket}/{key}"),
+        )
+        .unwrap();
+
+        assert!(!batch.ordered);
+        assert_eq!(batch.report, Report::Results);
+        let item = &batch.items[0];
+        assert_eq!(item.id, "s3://uploads/photos/summer 2024/café?.jpg");
+        assert_eq!(
+            item.route,
+            Some((
+                Method::PUT,
+                "/objects/uploads/photos/summer%202024/caf%C3%A9%3F.jpg".to_string()
+            ))
+        );
+        assert_eq!(item.headers["x-amzn-s3-event-name"], "ObjectCreated:Put");
+        assert_eq!(item.headers["x-amzn-s3-bucket"], "uploads");
+        assert_eq!(item.headers["x-amzn-s3-object-size"], "1024");
+        assert_eq!(
+            item.headers["x-amzn-s3-object-etag"],
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            item.headers["x-amzn-s3-key"].as_bytes(),
+            "photos/summer 2024/café?.jpg".as_bytes()
+        );
+        assert_eq!(item.headers["content-type"], "application/json");
+    }
+
+    #[test]
+    fn test_object_route() {
+        assert_eq!(ObjectRoute::new("invalid"), ObjectRoute::default());
+        assert_eq!(ObjectRoute::new("POST s3"), ObjectRoute::default());
+        assert_eq!(
+            ObjectRoute::new(" GET /files/{key} ").resolve("bucket", "a b/c#d"),
+            Some((Method::GET, "/files/a%20b/c%23d".to_string()))
+        );
+        let route = ObjectRoute::default();
+        assert_eq!(route.resolve("uploads", "../../admin/reset"), None);
+        assert_eq!(route.resolve("uploads", "reports/./q1.csv"), None);
+        assert_eq!(route.resolve("..", "q1.csv"), None);
+        // an encoded dot segment in the key is escaped as is
+        assert_eq!(
+            route.resolve("uploads", "%2E%2E/admin"),
+            Some((Method::POST, "/s3/uploads/%252E%252E/admin".to_string()))
+        );
+        assert_eq!(
+            route.resolve("uploads", "..hidden/q1..csv"),
+            Some((Method::POST, "/s3/uploads/..hidden/q1..csv".to_string()))
+        );
+    }
+}
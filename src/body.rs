@@ -0,0 +1,159 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics;
+use crate::shutdown::InFlightGuard;
+use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use http_body_util::Full;
+use hyper::body::Incoming;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tracing::Span;
+
+/// Response body returned by the adapter.
+///
+/// Either streams the body of the web application's response, or carries a
+/// complete body generated by the adapter itself (e.g. a `504 Gateway Timeout`).
+#[derive(Debug)]
+pub struct AdapterBody {
+    kind: Kind,
+    /// Keeps the invocation in flight until the body has been consumed.
+    in_flight: Option<InFlightGuard>,
+    trace: Option<BodyTrace>,
+    /// Emitted once the body has been sent or dropped.
+    metrics: Option<metrics::Invocation>,
+}
+
+/// Spans kept open while the body is sent, so that streamed responses are covered.
+#[derive(Debug)]
+struct BodyTrace {
+    invocation: Span,
+    stream: Span,
+    bytes: u64,
+}
+
+#[derive(Debug)]
+enum Kind {
+    App(Incoming),
+    Full(Full<Bytes>),
+}
+
+impl AdapterBody {
+    /// Creates a body generated by the adapter from the given bytes.
+    pub fn full(data: impl Into<Bytes>) -> Self {
+        AdapterBody {
+            kind: Kind::Full(Full::new(data.into())),
+            in_flight: None,
+            trace: None,
+            metrics: None,
+        }
+    }
+
+    /// Ties the invocation's in-flight guard to this body, so that shutdown
+    /// waits for streamed responses to be fully sent.
+    pub(crate) fn with_in_flight(mut self, guard: InFlightGuard) -> Self {
+        self.in_flight = Some(guard);
+        self
+    }
+
+    /// Counts the body bytes into the invocation's metrics, emitted when the body is dropped.
+    pub(crate) fn with_metrics(mut self, metrics: Option<metrics::Invocation>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Sends the body within a `response_stream` span, a child of the `invocation` span.
+    ///
+    /// Both spans stay open until the body has been sent or dropped, and the number
+    /// of body bytes is recorded on them as `http.response.body.size`.
+    pub(crate) fn with_span(mut self, invocation: Span) -> Self {
+        let stream = tracing::info_span!(
+            parent: &invocation,
+            "response_stream",
+            http.response.body.size = tracing::field::Empty,
+        );
+        self.trace = Some(BodyTrace {
+            invocation,
+            stream,
+            bytes: 0,
+        });
+        self
+    }
+}
+
+impl Drop for BodyTrace {
+    /// Ends the spans, and exports them before Lambda freezes the execution environment.
+    fn drop(&mut self) {
+        drop(std::mem::replace(&mut self.stream, Span::none()));
+        drop(std::mem::replace(&mut self.invocation, Span::none()));
+        #[cfg(feature = "otel")]
+        crate::otel::spawn_flush_invocation();
+    }
+}
+
+impl From<Incoming> for AdapterBody {
+    fn from(body: Incoming) -> Self {
+        AdapterBody {
+            kind: Kind::App(body),
+            in_flight: None,
+            trace: None,
+            metrics: None,
+        }
+    }
+}
+
+impl HttpBody for AdapterBody {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let kind = &mut this.kind;
+        let mut poll = || match kind {
+            Kind::App(body) => Pin::new(body).poll_frame(cx),
+            Kind::Full(body) => Pin::new(body).poll_frame(cx).map_err(|never| match never {}),
+        };
+        let frame = match &this.trace {
+            Some(trace) => trace.stream.in_scope(poll),
+            None => poll(),
+        };
+
+        if let (Some(metrics), Poll::Ready(Some(Ok(frame)))) = (&mut this.metrics, &frame) {
+            metrics.response_bytes += frame.data_ref().map_or(0, |data| data.len() as u64);
+        }
+        if let Some(trace) = &mut this.trace {
+            match &frame {
+                Poll::Ready(Some(Ok(frame))) => {
+                    trace.bytes += frame.data_ref().map_or(0, |data| data.len() as u64);
+                }
+                Poll::Ready(None) => {
+                    trace.stream.record("http.response.body.size", trace.bytes);
+                    trace.invocation.record("http.response.body.size", trace.bytes);
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    tracing::warn!(error = %e, "failed to read response body from app");
+                    trace.invocation.record("otel.status_code", "ERROR");
+                }
+                Poll::Pending => {}
+            }
+        }
+        frame
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.kind {
+            Kind::App(body) => body.is_end_stream(),
+            Kind::Full(body) => body.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.kind {
+            Kind::App(body) => body.size_hint(),
+            Kind::Full(body) => body.size_hint(),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use http::Uri;
+use hyper::rt::{Read, ReadBufCursor, Write};
+use hyper_util::client::legacy::connect::{Connected, Connection, HttpConnector};
+use hyper_util::rt::TokioIo;
+use lambda_http::Error;
+use std::{
+    future::Future,
+    io,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::net::{TcpStream, UnixStream};
+use tower::Service;
+
+/// Connector used to reach the web application.
+///
+/// Connects over TCP with an [`HttpConnector`] by default, or over a Unix domain
+/// socket when `AWS_LWA_UNIX_SOCKET` is set. Unix socket connections ignore the
+/// request URI; it is still used for the `Host` header and the connection pool key.
+#[derive(Clone, Debug)]
+pub enum AppConnector {
+    /// Connect to the application over TCP.
+    Tcp(HttpConnector),
+    /// Connect to the application over the Unix domain socket at this path.
+    Unix(Arc<PathBuf>),
+}
+
+impl AppConnector {
+    /// Creates a connector for the optional Unix domain socket path.
+    pub fn new(unix_socket: Option<&str>) -> Self {
+        match unix_socket {
+            Some(path) => AppConnector::Unix(Arc::new(PathBuf::from(path))),
+            None => AppConnector::Tcp(HttpConnector::new()),
+        }
+    }
+}
+
+impl Service<Uri> for AppConnector {
+    type Response = AppStream;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self {
+            AppConnector::Tcp(connector) => connector.poll_ready(cx).map_err(Error::from),
+            AppConnector::Unix(_) => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        match self {
+            AppConnector::Tcp(connector) => {
+                let connecting = connector.call(uri);
+                Box::pin(async move { Ok(AppStream::Tcp(connecting.await?)) })
+            }
+            AppConnector::Unix(path) => {
+                let path = path.clone();
+                Box::pin(async move {
+                    let stream = UnixStream::connect(path.as_path()).await?;
+                    Ok(AppStream::Unix(TokioIo::new(stream)))
+                })
+            }
+        }
+    }
+}
+
+/// A connection to the web application, established by [`AppConnector`].
+#[derive(Debug)]
+pub enum AppStream {
+    /// A TCP connection.
+    Tcp(TokioIo<TcpStream>),
+    /// A Unix domain socket connection.
+    Unix(TokioIo<UnixStream>),
+}
+
+impl Connection for AppStream {
+    fn connected(&self) -> Connected {
+        match self {
+            AppStream::Tcp(stream) => stream.connected(),
+            AppStream::Unix(stream) => stream.connected(),
+        }
+    }
+}
+
+impl Read for AppStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: ReadBufCursor<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            AppStream::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            AppStream::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl Write for AppStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            AppStream::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            AppStream::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            AppStream::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            AppStream::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            AppStream::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            AppStream::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            AppStream::Tcp(stream) => stream.is_write_vectored(),
+            AppStream::Unix(stream) => stream.is_write_vectored(),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            AppStream::Tcp(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+            AppStream::Unix(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::forwarded::alb_client_ip;
+use crate::ContextHeader;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use lambda_http::request::RequestContext;
+use serde_json::Value;
+
+/// Fields of the REST API authorizer that are not Lambda authorizer context keys.
+const REST_AUTHORIZER_FIELDS: [&str; 3] = ["claims", "principalId", "integrationLatency"];
+
+/// Fields of a request context, gathered from the API Gateway or ALB event.
+#[derive(Debug, Default)]
+struct ContextFields<'a> {
+    claims: Vec<(&'a str, String)>,
+    authorizer: Vec<(&'a str, String)>,
+    source_ip: Option<&'a str>,
+    user_agent: Option<&'a str>,
+    principal_id: Option<&'a str>,
+}
+
+impl<'a> ContextFields<'a> {
+    fn new(context: &'a RequestContext, headers: &'a HeaderMap) -> Self {
+        match context {
+            RequestContext::ApiGatewayV1(context) => {
+                let fields = &context.authorizer.fields;
+                ContextFields {
+                    claims: match fields.get("claims") {
+                        Some(Value::Object(claims)) => claims
+                            .iter()
+                            .map(|(name, value)| (name.as_str(), json_value(value)))
+                            .collect(),
+                        _ => Vec::new(),
+                    },
+                    authorizer: fields
+                        .iter()
+                        .filter(|(key, _)| !REST_AUTHORIZER_FIELDS.contains(&key.as_str()))
+                        .map(|(key, value)| (key.as_str(), json_value(value)))
+                        .collect(),
+                    source_ip: context.identity.source_ip.as_deref(),
+                    user_agent: context.identity.user_agent.as_deref(),
+                    principal_id: fields
+                        .get("principalId")
+                        .and_then(Value::as_str)
+                        .or(context.identity.user_arn.as_deref()),
+                }
+            }
+            RequestContext::ApiGatewayV2(context) => {
+                let authorizer = context.authorizer.as_ref();
+                ContextFields {
+                    claims: authorizer
+                        .and_then(|authorizer| authorizer.jwt.as_ref())
+                        .map(|jwt| {
+                            jwt.claims
+                                .iter()
+                                .map(|(name, value)| (name.as_str(), value.clone()))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    authorizer: authorizer
+                        .map(|authorizer| {
+                            authorizer
+                                .fields
+                                .iter()
+                                .map(|(key, value)| (key.as_str(), json_value(value)))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    source_ip: context.http.source_ip.as_deref(),
+                    user_agent: context.http.user_agent.as_deref(),
+                    principal_id: authorizer
+                        .and_then(|authorizer| authorizer.iam.as_ref())
+                        .and_then(|iam| iam.user_arn.as_deref()),
+                }
+            }
+            // ALB only forwards the client details in headers
+            RequestContext::Alb(_) => ContextFields {
+                source_ip: alb_client_ip(headers),
+                user_agent: headers.get("user-agent").and_then(|value| value.to_str().ok()),
+                ..Default::default()
+            },
+            _ => ContextFields::default(),
+        }
+    }
+}
+
+/// Adds the selected fields of the request context to `headers`, as `<prefix>claim-<name>`,
+/// `<prefix>authorizer-<key>`, `<prefix>source-ip`, `<prefix>user-agent` and `<prefix>principal-id`.
+///
+/// Headers sent by the client under these names are removed first, so the app can trust them.
+pub(crate) fn insert_context_headers(
+    selected: &[ContextHeader],
+    prefix: &str,
+    context: &RequestContext,
+    headers: &mut HeaderMap,
+) {
+    if selected.is_empty() {
+        return;
+    }
+
+    let mut context_headers = HeaderMap::new();
+    {
+        let fields = ContextFields::new(context, headers);
+        let mut add = |name: &str, value: &[u8]| match (
+            HeaderName::try_from(header_name(prefix, name)),
+            HeaderValue::from_bytes(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                context_headers.append(name, value);
+            }
+            _ => tracing::debug!(name, "request context field is not a valid header, skipping"),
+        };
+
+        for field in selected {
+            match field {
+                ContextHeader::Claims => {
+                    for (name, value) in &fields.claims {
+                        add(&format!("claim-{name}"), value.as_bytes());
+                    }
+                }
+                ContextHeader::Authorizer => {
+                    for (key, value) in &fields.authorizer {
+                        add(&format!("authorizer-{key}"), value.as_bytes());
+                    }
+                }
+                ContextHeader::SourceIp => fields
+                    .source_ip
+                    .into_iter()
+                    .for_each(|ip| add("source-ip", ip.as_bytes())),
+                ContextHeader::UserAgent => fields
+                    .user_agent
+                    .into_iter()
+                    .for_each(|user_agent| add("user-agent", user_agent.as_bytes())),
+                ContextHeader::PrincipalId => fields
+                    .principal_id
+                    .into_iter()
+                    .for_each(|principal_id| add("principal-id", principal_id.as_bytes())),
+            }
+        }
+    }
+
+    let spoofed: Vec<HeaderName> = headers
+        .keys()
+        .filter(|name| {
+            let Some(name) = name.as_str().strip_prefix(prefix) else {
+                return false;
+            };
+            selected.iter().any(|field| match field {
+                ContextHeader::Claims => name.starts_with("claim-"),
+                ContextHeader::Authorizer => name.starts_with("authorizer-"),
+                ContextHeader::SourceIp => name == "source-ip",
+                ContextHeader::UserAgent => name == "user-agent",
+                ContextHeader::PrincipalId => name == "principal-id",
+            })
+        })
+        .cloned()
+        .collect();
+    for name in spoofed {
+        headers.remove(name);
+    }
+    headers.extend(context_headers);
+}
+
+/// Builds a header name from the prefix and a field name, replacing characters that
+/// are not allowed in header names, e.g. `cognito:groups` becomes `cognito-groups`.
+pub(crate) fn header_name(prefix: &str, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{prefix}{name}")
+}
+
+/// Strings as is, other values as JSON.
+fn json_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ContextHeader; 5] = [
+        ContextHeader::Claims,
+        ContextHeader::Authorizer,
+        ContextHeader::SourceIp,
+        ContextHeader::UserAgent,
+        ContextHeader::PrincipalId,
+    ];
+
+    fn context_headers(event: &str, headers: &[(&str, &str)]) -> HeaderMap {
+        let request = lambda_http::request::from_str(event).unwrap();
+        let mut request_headers = request.headers().clone();
+        for (name, value) in headers {
+            request_headers.insert(
+                HeaderName::try_from(*name).unwrap(),
+                HeaderValue::try_from(*value).unwrap(),
+            );
+        }
+        let context = lambda_http::RequestExt::request_context(&request);
+        insert_context_headers(&ALL, "x-amzn-", &context, &mut request_headers);
+        request_headers
+    }
+
+    #[test]
+    fn test_rest_api_context_headers() {
+        let event = r#"{
+            "resource": "/",
+            "path": "/",
+            "httpMethod": "GET",
+            "headers": {},
+            "multiValueHeaders": {},
+            "requestContext": {
+                "resourcePath": "/",
+                "httpMethod": "GET",
+                "path": "/",
+                "stage": "prod",
+                "requestId": "id",
+                "identity": { "sourceIp": "203.0.113.1", "userAgent": "curl/8.0" },
+                "authorizer": {
+                    "claims": { "sub": "42", "cognito:groups": "admin" },
+                    "principalId": "user-42",
+                    "integrationLatency": 0,
+                    "tenant": "acme"
+                }
+            },
+            "body": null,
+            "isBase64Encoded": false
+        }"#;
+        let headers = context_headers(event, &[("x-amzn-claim-role", "spoofed")]);
+
+        assert_eq!(headers["x-amzn-claim-sub"], "42");
+        assert_eq!(headers["x-amzn-claim-cognito-groups"], "admin");
+        assert!(!headers.contains_key("x-amzn-claim-role"));
+        assert_eq!(headers["x-amzn-authorizer-tenant"], "acme");
+        assert!(!headers.contains_key("x-amzn-authorizer-principalid"));
+        assert_eq!(headers["x-amzn-source-ip"], "203.0.113.1");
+        assert_eq!(headers["x-amzn-user-agent"], "curl/8.0");
+        assert_eq!(headers["x-amzn-principal-id"], "user-42");
+    }
+
+    #[test]
+    fn test_http_api_context_headers() {
+        let event = r#"{
+            "version": "2.0",
+            "routeKey": "$default",
+            "rawPath": "/",
+            "rawQueryString": "",
+            "headers": {},
+            "requestContext": {
+                "accountId": "123456789012",
+                "apiId": "abc",
+                "authorizer": {
+                    "jwt": { "claims": { "sub": "42", "email": "jane@example.com" }, "scopes": null },
+                    "lambda": { "tenant": "acme", "admin": true }
+                },
+                "domainName": "abc.execute-api.us-east-1.amazonaws.com",
+                "http": { "method": "GET", "path": "/", "protocol": "HTTP/1.1", "sourceIp": "203.0.113.1", "userAgent": "curl/8.0" },
+                "requestId": "id",
+                "routeKey": "$default",
+                "stage": "$default",
+                "time": "12/Mar/2020:19:03:58 +0000",
+                "timeEpoch": 1583348638390
+            },
+            "isBase64Encoded": false
+        }"#;
+        let headers = context_headers(event, &[]);
+
+        assert_eq!(headers["x-amzn-claim-sub"], "42");
+        assert_eq!(headers["x-amzn-claim-email"], "jane@example.com");
+        assert_eq!(headers["x-amzn-authorizer-tenant"], "acme");
+        assert_eq!(headers["x-amzn-authorizer-admin"], "true");
+        assert_eq!(headers["x-amzn-source-ip"], "203.0.113.1");
+        assert_eq!(headers["x-amzn-user-agent"], "curl/8.0");
+        assert!(!headers.contains_key("x-amzn-principal-id"));
+    }
+
+    #[test]
+    fn test_alb_context_headers() {
+        let event = r#"{
+            "requestContext": { "elb": { "targetGroupArn": "arn:aws:elasticloadbalancing:us-east-1:123456789012:targetgroup/tg/1" } },
+            "httpMethod": "GET",
+            "path": "/",
+            "queryStringParameters": {},
+            "headers": { "x-forwarded-for": "198.51.100.1, 203.0.113.1", "user-agent": "curl/8.0" },
+            "body": "",
+            "isBase64Encoded": false
+        }"#;
+        let headers = context_headers(event, &[("x-amzn-source-ip", "198.51.100.1")]);
+
+        assert_eq!(headers["x-amzn-source-ip"], "203.0.113.1");
+        assert_eq!(headers["x-amzn-user-agent"], "curl/8.0");
+        assert!(!headers.keys().any(|name| name.as_str().starts_with("x-amzn-claim-")));
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::context_headers::header_name;
+use crate::events::{content_type, insert_header};
+use crate::EventSource;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+
+/// The payload of an event, taken out of its Lambda envelope, with the envelope
+/// metadata as headers.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Unwrapped {
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Vec<u8>,
+}
+
+/// Event sources whose events can be unwrapped.
+pub(crate) const SOURCES: [EventSource; 2] = [EventSource::Sns, EventSource::EventBridge];
+
+/// Unwraps the payload of an event, if events of `source` can be unwrapped: the SNS
+/// `Message`, or the EventBridge `detail`.
+pub(crate) fn unwrap(source: EventSource, event: &Value) -> Option<Unwrapped> {
+    let mut unwrapped = match source {
+        EventSource::Sns => sns(event)?,
+        EventSource::EventBridge => eventbridge(event)?,
+        _ => return None,
+    };
+    unwrapped.headers.insert(
+        http::header::CONTENT_TYPE,
+        HeaderValue::from_static(content_type(&unwrapped.body)),
+    );
+    Some(unwrapped)
+}
+
+fn sns(event: &Value) -> Option<Unwrapped> {
+    // Lambda is invoked with one SNS message at a time
+    let sns = event.get("Records")?.get(0)?.get("Sns")?;
+    let message = sns.get("Message")?.as_str()?;
+
+    let mut headers = HeaderMap::new();
+    insert_header(&mut headers, "x-amzn-sns-topic-arn", sns["TopicArn"].as_str());
+    insert_header(&mut headers, "x-amzn-sns-message-id", sns["MessageId"].as_str());
+    insert_header(&mut headers, "x-amzn-sns-subject", sns["Subject"].as_str());
+    insert_header(&mut headers, "x-amzn-sns-timestamp", sns["Timestamp"].as_str());
+    for (name, attribute) in sns["MessageAttributes"].as_object().into_iter().flatten() {
+        let header = HeaderName::try_from(header_name("x-amzn-sns-attribute-", name));
+        let value = attribute["Value"].as_str().map(HeaderValue::from_str);
+        match (header, value) {
+            (Ok(header), Some(Ok(value))) => {
+                headers.insert(header, value);
+            }
+            _ => tracing::debug!(name, "SNS message attribute is not a valid header, skipping"),
+        }
+    }
+
+    Some(Unwrapped {
+        headers,
+        body: message.as_bytes().to_vec(),
+    })
+}
+
+fn eventbridge(event: &Value) -> Option<Unwrapped> {
+    let detail = event.get("detail")?;
+
+    let mut headers = HeaderMap::new();
+    for (name, field) in [
+        ("x-amzn-eventbridge-id", "id"),
+        ("x-amzn-eventbridge-detail-type", "detail-type"),
+        ("x-amzn-eventbridge-source", "source"),
+        ("x-amzn-eventbridge-time", "time"),
+        ("x-amzn-eventbridge-account", "account"),
+        ("x-amzn-eventbridge-region", "region"),
+    ] {
+        insert_header(&mut headers, name, event[field].as_str());
+    }
+    let resources: Vec<&str> = event["resources"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    if !resources.is_empty() {
+        insert_header(&mut headers, "x-amzn-eventbridge-resources", Some(&resources.join(",")));
+    }
+
+    Some(Unwrapped {
+        headers,
+        body: detail.to_string().into_bytes(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unwrap_sns() {
+        let event = json!({
+            "Records": [{
+                "EventSource": "aws:sns",
+                "Sns": {
+                    "Type": "Notification",
+                    "MessageId": "95df01b4-ee98-5cb9-9903-4c221d41eb5e",
+                    "TopicArn": "arn:aws:sns:us-east-1:123456789012:orders",
+                    "Subject": "New order",
+                    "Message": "{\"order\":42}",
+                    "Timestamp": "2024-01-01T00:00:00.000Z",
+                    "MessageAttributes": {
+                        "Tenant": { "Type": "String", "Value": "acme" },
+                        "retry.count": { "Type": "Number", "Value": "1" }
+                    }
+                }
+            }]
+        });
+        let unwrapped = unwrap(EventSource::Sns, &event).unwrap();
+
+        assert_eq!(unwrapped.body, b"{\"order\":42}");
+        assert_eq!(unwrapped.headers["content-type"], "application/json");
+        assert_eq!(
+            unwrapped.headers["x-amzn-sns-topic-arn"],
+            "arn:aws:sns:us-east-1:123456789012:orders"
+        );
+        assert_eq!(
+            unwrapped.headers["x-amzn-sns-message-id"],
+            "95df01b4-ee98-5cb9-9903-4c221d41eb5e"
+        );
+        assert_eq!(unwrapped.headers["x-amzn-sns-subject"], "New order");
+        assert_eq!(unwrapped.headers["x-amzn-sns-attribute-tenant"], "acme");
+        assert_eq!(unwrapped.headers["x-amzn-sns-attribute-retry-count"], "1");
+    }
+
+    #[test]
+    fn test_unwrap_eventbridge() {
+        let event = json!({
+            "version": "0",
+            "id": "6a7e8feb-b491-4cf7-a9f1-bf3703467718",
+            "detail-type": "Object Created",
+            "source": "aws.s3",
+            "account": "123456789012",
+            "time": "2024-01-01T00:00:00Z",
+            "region": "us-east-1",
+            "resources": ["arn:aws:s3:::bucket-a", "arn:aws:s3:::bucket-b"],
+            "detail": { "bucket": { "name": "bucket-a" } }
+        });
+        let unwrapped = unwrap(EventSource::EventBridge, &event).unwrap();
+
+        assert_eq!(unwrapped.body, b"{\"bucket\":{\"name\":\"bucket-a\"}}");
+        assert_eq!(unwrapped.headers["content-type"], "application/json");
+        assert_eq!(unwrapped.headers["x-amzn-eventbridge-detail-type"], "Object Created");
+        assert_eq!(unwrapped.headers["x-amzn-eventbridge-source"], "aws.s3");
+        assert_eq!(unwrapped.headers["x-amzn-eventbridge-region"], "us-east-1");
+        assert_eq!(
+            unwrapped.headers["x-amzn-eventbridge-resources"],
+            "arn:aws:s3:::bucket-a,arn:aws:s3:::bucket-b"
+        );
+
+        assert_eq!(unwrap(EventSource::Sqs, &event), None);
+        assert_eq!(unwrap(EventSource::EventBridge, &json!({ "source": "aws.s3" })), None);
+    }
+}
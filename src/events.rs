@@ -0,0 +1,278 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::fmt;
+
+/// Source of a non-HTTP event, detected from its payload.
+///
+/// Sent to the app in the `x-amzn-event-source` header, and used as the key of
+/// `AWS_LWA_PASS_THROUGH_ROUTES`.
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::EventSource;
+///
+/// assert_eq!(EventSource::EventBridge.to_string(), "eventbridge");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EventSource {
+    /// `sqs`: Amazon SQS messages.
+    Sqs,
+    /// `sns`: Amazon SNS notifications.
+    Sns,
+    /// `s3`: Amazon S3 event notifications.
+    S3,
+    /// `dynamodb`: DynamoDB Streams records.
+    DynamoDb,
+    /// `kinesis`: Kinesis Data Streams records.
+    Kinesis,
+    /// `kafka`: Amazon MSK or self-managed Kafka records.
+    Kafka,
+    /// `eventbridge`: EventBridge events, including scheduled events.
+    EventBridge,
+    /// `bedrock_agent`: Bedrock Agents action group invocations.
+    BedrockAgent,
+    /// `unknown`: any other payload.
+    Unknown,
+}
+
+impl EventSource {
+    const ALL: [EventSource; 9] = [
+        EventSource::Sqs,
+        EventSource::Sns,
+        EventSource::S3,
+        EventSource::DynamoDb,
+        EventSource::Kinesis,
+        EventSource::Kafka,
+        EventSource::EventBridge,
+        EventSource::BedrockAgent,
+        EventSource::Unknown,
+    ];
+
+    /// Name of the event source, e.g. `sqs`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventSource::Sqs => "sqs",
+            EventSource::Sns => "sns",
+            EventSource::S3 => "s3",
+            EventSource::DynamoDb => "dynamodb",
+            EventSource::Kinesis => "kinesis",
+            EventSource::Kafka => "kafka",
+            EventSource::EventBridge => "eventbridge",
+            EventSource::BedrockAgent => "bedrock_agent",
+            EventSource::Unknown => "unknown",
+        }
+    }
+
+    /// Returns the event source named `name`, e.g. `sqs`.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        EventSource::ALL.into_iter().find(|source| source.as_str() == name)
+    }
+
+    /// Detects the source of an event payload.
+    pub(crate) fn detect(event: &Value) -> Self {
+        let record_source = event
+            .get("Records")
+            .and_then(|records| records.get(0))
+            // SNS records spell it `EventSource`
+            .and_then(|record| record.get("eventSource").or_else(|| record.get("EventSource")))
+            .and_then(Value::as_str);
+        match record_source {
+            Some("aws:sqs") => return EventSource::Sqs,
+            Some("aws:sns") => return EventSource::Sns,
+            Some("aws:s3") => return EventSource::S3,
+            Some("aws:dynamodb") => return EventSource::DynamoDb,
+            Some("aws:kinesis") => return EventSource::Kinesis,
+            _ => {}
+        }
+        let event_source = event.get("eventSource").and_then(Value::as_str);
+        if matches!(event_source, Some("aws:kafka" | "SelfManagedKafka")) {
+            EventSource::Kafka
+        } else if event.get("detail-type").is_some() && event.get("source").is_some() {
+            EventSource::EventBridge
+        } else if event.get("agent").is_some() && event.get("actionGroup").is_some() {
+            EventSource::BedrockAgent
+        } else {
+            EventSource::Unknown
+        }
+    }
+}
+
+impl fmt::Display for EventSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses a comma-separated list of `source=path` pass-through routes:
+///
+/// ```text
+/// sqs=/events/sqs, eventbridge=/events/eb/{detail-type}
+/// ```
+///
+/// Invalid entries are logged as warnings and skipped.
+pub(crate) fn parse_pass_through_routes(input: &str) -> Vec<(EventSource, String)> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let route = entry.split_once('=').and_then(|(name, path)| {
+                let name = name.trim().to_lowercase();
+                let source = EventSource::from_name(&name)?;
+                let path = path.trim();
+                path.starts_with('/').then(|| (source, path.to_string()))
+            });
+            if route.is_none() {
+                tracing::warn!("Invalid pass-through route: {}", entry);
+            }
+            route
+        })
+        .collect()
+}
+
+/// Returns the path of the route for `source`, with `{field}` placeholders replaced.
+///
+/// Placeholders are replaced with the string field of that name at the top level of the
+/// event, or else in its first record, e.g. `{detail-type}` or `{eventSourceARN}`. Returns
+/// `None` if there is no route for `source`, or a placeholder doesn't resolve. A value of
+/// `.` or `..` doesn't resolve: the URL would normalise it and escape the route.
+pub(crate) fn route(routes: &[(EventSource, String)], source: EventSource, event: &Value) -> Option<String> {
+    let (_, template) = routes.iter().find(|(route_source, _)| *route_source == source)?;
+    let record = event.get("Records").and_then(|records| records.get(0));
+
+    let mut path = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let field = &rest[start + 1..start + end];
+        let value = event
+            .get(field)
+            .or_else(|| record.and_then(|record| record.get(field)))
+            .and_then(Value::as_str);
+        let Some(value) = value else {
+            tracing::warn!(source = %source, field, "pass-through route field not found in event");
+            return None;
+        };
+        // `%2E%2E` is a dot segment too, so these can't be escaped
+        if is_dot_segment(value) {
+            tracing::warn!(source = %source, field, value, "pass-through route field is a dot segment");
+            return None;
+        }
+        path.push_str(&rest[..start]);
+        // keep the value in one path segment
+        path.push_str(&value.replace('%', "%25").replace('/', "%2F"));
+        rest = &rest[start + end + 1..];
+    }
+    path.push_str(rest);
+    Some(path)
+}
+
+/// Content type of a record or message body: JSON, other text, or binary.
+pub(crate) fn content_type(body: &[u8]) -> &'static str {
+    if serde_json::from_slice::<Value>(body).is_ok() {
+        "application/json"
+    } else if std::str::from_utf8(body).is_ok() {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Inserts a header describing an event, if the value is present and valid.
+pub(crate) fn insert_header(headers: &mut HeaderMap, name: &'static str, value: Option<&str>) {
+    if let Some(value) = value.and_then(|value| HeaderValue::from_str(value).ok()) {
+        headers.insert(HeaderName::from_static(name), value);
+    }
+}
+
+/// Whether a path segment is `.` or `..`, which URLs normalise away.
+pub(crate) fn is_dot_segment(segment: &str) -> bool {
+    segment == "." || segment == ".."
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_event_source() {
+        let records = |key: &str, source: &str| json!({ "Records": [{ key: source }] });
+        assert_eq!(
+            EventSource::detect(&records("eventSource", "aws:sqs")),
+            EventSource::Sqs
+        );
+        assert_eq!(
+            EventSource::detect(&records("EventSource", "aws:sns")),
+            EventSource::Sns
+        );
+        assert_eq!(EventSource::detect(&records("eventSource", "aws:s3")), EventSource::S3);
+        assert_eq!(
+            EventSource::detect(&records("eventSource", "aws:dynamodb")),
+            EventSource::DynamoDb
+        );
+        assert_eq!(
+            EventSource::detect(&records("eventSource", "aws:kinesis")),
+            EventSource::Kinesis
+        );
+        assert_eq!(
+            EventSource::detect(&json!({ "eventSource": "aws:kafka", "records": {} })),
+            EventSource::Kafka
+        );
+        assert_eq!(
+            EventSource::detect(&json!({ "source": "aws.events", "detail-type": "Scheduled Event", "detail": {} })),
+            EventSource::EventBridge
+        );
+        assert_eq!(
+            EventSource::detect(&json!({ "messageVersion": "1.0", "agent": {}, "actionGroup": "orders" })),
+            EventSource::BedrockAgent
+        );
+        assert_eq!(EventSource::detect(&json!({ "hello": "world" })), EventSource::Unknown);
+        assert_eq!(EventSource::detect(&Value::Null), EventSource::Unknown);
+    }
+
+    #[test]
+    fn test_parse_pass_through_routes() {
+        assert_eq!(
+            parse_pass_through_routes("sqs=/events/sqs, EventBridge = /events/eb/{detail-type},ses=/ses,s3=events,sns"),
+            vec![
+                (EventSource::Sqs, "/events/sqs".to_string()),
+                (EventSource::EventBridge, "/events/eb/{detail-type}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_route() {
+        let routes = parse_pass_through_routes("sqs=/events/sqs/{eventSourceARN},eventbridge=/events/eb/{detail-type}");
+        let event = json!({ "source": "aws.s3", "detail-type": "Object Created", "detail": {} });
+        assert_eq!(
+            route(&routes, EventSource::EventBridge, &event),
+            Some("/events/eb/Object Created".to_string())
+        );
+
+        let event = json!({ "Records": [{ "eventSource": "aws:sqs", "eventSourceARN": "arn:aws:sqs:us-east-1:123456789012:orders/1" }] });
+        assert_eq!(
+            route(&routes, EventSource::Sqs, &event),
+            Some("/events/sqs/arn:aws:sqs:us-east-1:123456789012:orders%2F1".to_string())
+        );
+
+        assert_eq!(route(&routes, EventSource::Sns, &event), None);
+        assert_eq!(route(&routes, EventSource::EventBridge, &event), None);
+
+        // a dot segment would escape the route prefix
+        let event = json!({ "source": "aws.s3", "detail-type": "..", "detail": {} });
+        assert_eq!(route(&routes, EventSource::EventBridge, &event), None);
+        let event = json!({ "source": "aws.s3", "detail-type": "Object..Created", "detail": {} });
+        assert_eq!(
+            route(&routes, EventSource::EventBridge, &event),
+            Some("/events/eb/Object..Created".to_string())
+        );
+    }
+}
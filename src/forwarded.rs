@@ -0,0 +1,295 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ForwardedHeaders, ForwardedStyle};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use lambda_http::request::RequestContext;
+
+/// Where a request came from, as seen by the API Gateway, function URL or ALB in front of Lambda.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Origin {
+    client_ip: Option<String>,
+    proto: Option<String>,
+    host: Option<String>,
+    port: Option<String>,
+}
+
+impl Origin {
+    /// Reads the origin of a request from its context, or for ALB from the headers the
+    /// ALB added, so it must be read before the client's headers are rewritten.
+    pub(crate) fn new(context: &RequestContext, headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        // API Gateway and function URLs only serve HTTPS
+        let https = || (Some("https".to_string()), Some("443".to_string()));
+        match context {
+            RequestContext::ApiGatewayV1(context) => {
+                let (proto, port) = https();
+                Origin {
+                    client_ip: context.identity.source_ip.clone(),
+                    proto,
+                    host: context.domain_name.clone().or_else(|| header("host")),
+                    port,
+                }
+            }
+            RequestContext::ApiGatewayV2(context) => {
+                let (proto, port) = https();
+                Origin {
+                    client_ip: context.http.source_ip.clone(),
+                    proto,
+                    host: context.domain_name.clone().or_else(|| header("host")),
+                    port,
+                }
+            }
+            RequestContext::Alb(_) => Origin {
+                client_ip: alb_client_ip(headers).map(str::to_string),
+                proto: header("x-forwarded-proto"),
+                host: header("host"),
+                port: header("x-forwarded-port"),
+            },
+            _ => Origin::default(),
+        }
+    }
+}
+
+/// The client IP address of an ALB request.
+///
+/// ALB appends the address of the peer it accepted the connection from to `X-Forwarded-For`,
+/// so the last entry is the only one the client can't forge.
+pub(crate) fn alb_client_ip(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .rfind(|ip| !ip.is_empty())
+}
+
+/// Adds `X-Forwarded-*` and/or `Forwarded` headers describing the client, the protocol,
+/// host and port it connected to, as described by `origin`.
+///
+/// In [`ForwardedHeaders::Append`] mode, the client is appended to the `X-Forwarded-For`
+/// and `Forwarded` values sent by the client, and `X-Forwarded-Proto`, `-Host` and `-Port`
+/// are only set when missing. In [`ForwardedHeaders::Overwrite`] mode, all are replaced.
+pub(crate) fn insert_forwarded_headers(
+    mode: ForwardedHeaders,
+    style: ForwardedStyle,
+    origin: &Origin,
+    headers: &mut HeaderMap,
+) {
+    if mode == ForwardedHeaders::Off {
+        return;
+    }
+    let overwrite = mode == ForwardedHeaders::Overwrite;
+
+    if style.x_forwarded() {
+        if let Some(client_ip) = origin.client_ip.as_deref() {
+            let existing = if overwrite {
+                None
+            } else {
+                joined(headers, "x-forwarded-for", ", ")
+            };
+            let value = match existing {
+                // ALB, API Gateway and function URLs already append the client themselves
+                Some(existing) if existing.rsplit(',').next().map(str::trim) == Some(client_ip) => existing,
+                Some(existing) => format!("{existing}, {client_ip}"),
+                None => client_ip.to_string(),
+            };
+            set(headers, "x-forwarded-for", &value);
+        }
+        for (name, value) in [
+            ("x-forwarded-proto", &origin.proto),
+            ("x-forwarded-host", &origin.host),
+            ("x-forwarded-port", &origin.port),
+        ] {
+            if let Some(value) = value {
+                if overwrite || !headers.contains_key(name) {
+                    set(headers, name, value);
+                }
+            }
+        }
+    }
+
+    if style.forwarded() {
+        let element = [
+            ("for", origin.client_ip.as_deref().map(node)),
+            ("proto", origin.proto.clone()),
+            ("host", origin.host.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| format!("{name}={}", quote(&value))))
+        .collect::<Vec<_>>()
+        .join(";");
+        if !element.is_empty() {
+            let value = match joined(headers, "forwarded", ", ").filter(|_| !overwrite) {
+                Some(existing) => format!("{existing}, {element}"),
+                None => element,
+            };
+            set(headers, "forwarded", &value);
+        }
+    }
+}
+
+/// All values of a header, joined with `separator`.
+fn joined(headers: &HeaderMap, name: &str, separator: &str) -> Option<String> {
+    let values: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    (!values.is_empty()).then(|| values.join(separator))
+}
+
+fn set(headers: &mut HeaderMap, name: &'static str, value: &str) {
+    match HeaderValue::from_str(value) {
+        Ok(value) => {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+        Err(_) => tracing::warn!(header = name, value, "forwarded header value is not valid, skipping"),
+    }
+}
+
+/// An RFC 7239 node: IPv6 addresses are enclosed in brackets.
+fn node(ip: &str) -> String {
+    if ip.contains(':') {
+        format!("[{ip}]")
+    } else {
+        ip.to_string()
+    }
+}
+
+/// An RFC 7239 value: a token, or else a quoted string.
+fn quote(value: &str) -> String {
+    let is_tchar = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if !value.is_empty() && value.chars().all(is_tchar) {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http_api_context() -> RequestContext {
+        let event = r#"{
+            "version": "2.0",
+            "routeKey": "$default",
+            "rawPath": "/",
+            "rawQueryString": "",
+            "headers": {},
+            "requestContext": {
+                "accountId": "123456789012",
+                "apiId": "abc",
+                "domainName": "abc.lambda-url.us-east-1.on.aws",
+                "http": { "method": "GET", "path": "/", "protocol": "HTTP/1.1", "sourceIp": "2001:db8::1", "userAgent": "curl/8.0" },
+                "requestId": "id",
+                "routeKey": "$default",
+                "stage": "$default",
+                "time": "12/Mar/2020:19:03:58 +0000",
+                "timeEpoch": 1583348638390
+            },
+            "isBase64Encoded": false
+        }"#;
+        lambda_http::RequestExt::request_context(&lambda_http::request::from_str(event).unwrap())
+    }
+
+    fn headers(entries: &[(&'static str, &'static str)]) -> HeaderMap {
+        entries
+            .iter()
+            .map(|(name, value)| (HeaderName::from_static(name), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_forwarded_headers_overwrite() {
+        let mut headers = headers(&[("x-forwarded-for", "198.51.100.1"), ("x-forwarded-proto", "http")]);
+        insert_forwarded_headers(
+            ForwardedHeaders::Overwrite,
+            ForwardedStyle::Both,
+            &Origin::new(&http_api_context(), &headers),
+            &mut headers,
+        );
+
+        assert_eq!(headers["x-forwarded-for"], "2001:db8::1");
+        assert_eq!(headers["x-forwarded-proto"], "https");
+        assert_eq!(headers["x-forwarded-host"], "abc.lambda-url.us-east-1.on.aws");
+        assert_eq!(headers["x-forwarded-port"], "443");
+        assert_eq!(
+            headers["forwarded"],
+            "for=\"[2001:db8::1]\";proto=https;host=abc.lambda-url.us-east-1.on.aws"
+        );
+    }
+
+    #[test]
+    fn test_forwarded_headers_append() {
+        let mut headers = headers(&[
+            ("x-forwarded-for", "198.51.100.1"),
+            ("x-forwarded-proto", "http"),
+            ("forwarded", "for=198.51.100.1"),
+        ]);
+        insert_forwarded_headers(
+            ForwardedHeaders::Append,
+            ForwardedStyle::Both,
+            &Origin::new(&http_api_context(), &headers),
+            &mut headers,
+        );
+
+        assert_eq!(headers["x-forwarded-for"], "198.51.100.1, 2001:db8::1");
+        assert_eq!(headers["x-forwarded-proto"], "http");
+        assert_eq!(headers["x-forwarded-port"], "443");
+        assert_eq!(
+            headers["forwarded"],
+            "for=198.51.100.1, for=\"[2001:db8::1]\";proto=https;host=abc.lambda-url.us-east-1.on.aws"
+        );
+
+        // the client is not appended twice
+        let mut headers = headers.clone();
+        insert_forwarded_headers(
+            ForwardedHeaders::Append,
+            ForwardedStyle::XForwarded,
+            &Origin::new(&http_api_context(), &headers),
+            &mut headers,
+        );
+        assert_eq!(headers["x-forwarded-for"], "198.51.100.1, 2001:db8::1");
+    }
+
+    #[test]
+    fn test_alb_forwarded_headers() {
+        let context = RequestContext::Alb(Default::default());
+        let mut headers = headers(&[
+            ("x-forwarded-for", "198.51.100.1, 203.0.113.1"),
+            ("x-forwarded-proto", "https"),
+            ("x-forwarded-port", "443"),
+            ("host", "example.com"),
+        ]);
+        insert_forwarded_headers(
+            ForwardedHeaders::Overwrite,
+            ForwardedStyle::Rfc7239,
+            &Origin::new(&context, &headers),
+            &mut headers,
+        );
+
+        assert_eq!(headers["x-forwarded-for"], "198.51.100.1, 203.0.113.1");
+        assert_eq!(headers["forwarded"], "for=203.0.113.1;proto=https;host=example.com");
+        assert!(!headers.contains_key("x-forwarded-host"));
+    }
+
+    #[test]
+    fn test_forwarded_headers_off() {
+        let mut headers = HeaderMap::new();
+        insert_forwarded_headers(
+            ForwardedHeaders::Off,
+            ForwardedStyle::Both,
+            &Origin::new(&http_api_context(), &headers),
+            &mut headers,
+        );
+        assert!(headers.is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+
+/// A rule rewriting the headers of requests before they are forwarded to the app,
+/// configured through `AWS_LWA_REQUEST_HEADER_RULES`.
+///
+/// Header name patterns are case-insensitive and may end with `*` to match a prefix,
+/// e.g. `x-forwarded-*`.
+///
+/// # Examples
+///
+/// ```rust
+/// use http::HeaderName;
+/// use lambda_web_adapter::HeaderRule;
+///
+/// let rule = HeaderRule::Set {
+///     name: HeaderName::from_static("x-user-id"),
+///     value: "${/authorizer/jwt/claims/sub}".to_string(),
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum HeaderRule {
+    /// Keeps only the headers matching the `allow` patterns.
+    ///
+    /// All `allow` rules together form one allowlist, applied before the other rules.
+    Allow(String),
+    /// Removes the headers matching the pattern.
+    Drop(String),
+    /// Moves the header to a new name, replacing any header with that name.
+    Rename {
+        /// Current header name.
+        from: HeaderName,
+        /// New header name.
+        to: HeaderName,
+    },
+    /// Sets the header, replacing any value sent by the client.
+    ///
+    /// `${/json/pointer}` placeholders in the value are replaced with the value at the
+    /// JSON pointer in the request context, the one in `x-amzn-request-context`. The
+    /// header is removed if a placeholder doesn't resolve.
+    Set {
+        /// Header name.
+        name: HeaderName,
+        /// Header value template.
+        value: String,
+    },
+}
+
+/// Parses a `;`-separated list of header rules:
+///
+/// ```text
+/// drop x-forwarded-*; rename x-api-token authorization; set x-env: production; set x-user-id: ${/authorizer/jwt/claims/sub}
+/// ```
+///
+/// Invalid entries are logged as warnings and skipped.
+pub(crate) fn parse_header_rules(input: &str) -> Vec<HeaderRule> {
+    input
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let rule = parse_header_rule(entry);
+            if rule.is_none() {
+                tracing::warn!("Invalid header rule: {}", entry);
+            }
+            rule
+        })
+        .collect()
+}
+
+fn parse_header_rule(entry: &str) -> Option<HeaderRule> {
+    let (action, args) = entry.split_once(char::is_whitespace)?;
+    let args = args.trim();
+    match action.to_lowercase().as_str() {
+        "allow" if is_pattern(args) => Some(HeaderRule::Allow(args.to_lowercase())),
+        "drop" if is_pattern(args) => Some(HeaderRule::Drop(args.to_lowercase())),
+        "rename" => {
+            let (from, to) = args.split_once(char::is_whitespace)?;
+            Some(HeaderRule::Rename {
+                from: HeaderName::try_from(from).ok()?,
+                to: HeaderName::try_from(to.trim()).ok()?,
+            })
+        }
+        "set" => {
+            let (name, value) = args.split_once(':')?;
+            Some(HeaderRule::Set {
+                name: HeaderName::try_from(name.trim()).ok()?,
+                value: value.trim().to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether `pattern` is a header name, optionally followed by `*`.
+fn is_pattern(pattern: &str) -> bool {
+    let name = pattern.strip_suffix('*').unwrap_or(pattern);
+    pattern == "*" || HeaderName::try_from(name).is_ok()
+}
+
+fn matches(pattern: &str, name: &HeaderName) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.as_str().starts_with(&prefix.to_ascii_lowercase()),
+        None => name.as_str().eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Applies `rules` to `headers`.
+///
+/// `request_context` is only called if a rule has a placeholder to resolve.
+pub(crate) fn apply_header_rules(
+    rules: &[HeaderRule],
+    headers: &mut HeaderMap,
+    request_context: impl FnOnce() -> Value,
+) {
+    let allowed: Vec<&str> = rules
+        .iter()
+        .filter_map(|rule| match rule {
+            HeaderRule::Allow(pattern) => Some(pattern.as_str()),
+            _ => None,
+        })
+        .collect();
+    if !allowed.is_empty() {
+        remove_matching(headers, |name| !allowed.iter().any(|pattern| matches(pattern, name)));
+    }
+
+    let mut request_context = Some(request_context);
+    let mut context = Value::Null;
+    for rule in rules {
+        match rule {
+            HeaderRule::Allow(_) => {}
+            HeaderRule::Drop(pattern) => remove_matching(headers, |name| matches(pattern, name)),
+            HeaderRule::Rename { from, to } => {
+                let values: Vec<HeaderValue> = headers.get_all(from).iter().cloned().collect();
+                if values.is_empty() {
+                    continue;
+                }
+                headers.remove(from);
+                headers.remove(to);
+                for value in values {
+                    headers.append(to.clone(), value);
+                }
+            }
+            HeaderRule::Set { name, value } => {
+                headers.remove(name);
+                if value.contains("${") {
+                    if let Some(request_context) = request_context.take() {
+                        context = request_context();
+                    }
+                }
+                match render(value, &context).map(|value| HeaderValue::from_str(&value)) {
+                    Some(Ok(value)) => {
+                        headers.insert(name.clone(), value);
+                    }
+                    Some(Err(_)) => {
+                        tracing::warn!(header = %name, "header rule value is not a valid header value, skipping")
+                    }
+                    None => {
+                        tracing::debug!(header = %name, "header rule placeholder not found in request context, skipping")
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn remove_matching(headers: &mut HeaderMap, predicate: impl Fn(&HeaderName) -> bool) {
+    let names: Vec<HeaderName> = headers.keys().filter(|name| predicate(name)).cloned().collect();
+    for name in names {
+        headers.remove(name);
+    }
+}
+
+/// Replaces the `${/json/pointer}` placeholders in `template` with the values in `context`.
+///
+/// Strings are inserted as is, other values as JSON. Returns `None` if a pointer doesn't resolve.
+fn render(template: &str, context: &Value) -> Option<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match context.pointer(&rest[start + 2..start + end])? {
+            Value::String(value) => rendered.push_str(value),
+            Value::Null => return None,
+            value => rendered.push_str(&value.to_string()),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+    Some(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_header_rules() {
+        let rules = parse_header_rules(
+            "allow x-amzn-*; DROP X-Forwarded-*; rename x-api-token authorization; set x-user-id: ${/authorizer/jwt/claims/sub};",
+        );
+        assert_eq!(
+            rules,
+            vec![
+                HeaderRule::Allow("x-amzn-*".to_string()),
+                HeaderRule::Drop("x-forwarded-*".to_string()),
+                HeaderRule::Rename {
+                    from: HeaderName::from_static("x-api-token"),
+                    to: HeaderName::from_static("authorization"),
+                },
+                HeaderRule::Set {
+                    name: HeaderName::from_static("x-user-id"),
+                    value: "${/authorizer/jwt/claims/sub}".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_header_rules_invalid() {
+        let rules = parse_header_rules("drop; rename x-only; set no-colon; drop bad header; keep x-a; drop x-b*");
+        assert_eq!(rules, vec![HeaderRule::Drop("x-b*".to_string())]);
+    }
+
+    #[test]
+    fn test_apply_header_rules() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.1"));
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("https"));
+        headers.insert("x-api-token", HeaderValue::from_static("token"));
+        headers.insert("x-user-id", HeaderValue::from_static("spoofed"));
+        headers.insert("x-tenant", HeaderValue::from_static("spoofed"));
+        headers.insert("accept", HeaderValue::from_static("*/*"));
+
+        let rules = parse_header_rules(
+            "drop x-forwarded-*; rename x-api-token authorization; set x-env: prod; \
+             set x-user-id: user-${/authorizer/jwt/claims/sub}; set x-admin: ${/authorizer/jwt/claims/admin}; \
+             set x-tenant: ${/authorizer/jwt/claims/tenant}",
+        );
+        let context = json!({ "authorizer": { "jwt": { "claims": { "sub": "42", "admin": true } } } });
+        apply_header_rules(&rules, &mut headers, || context);
+
+        assert!(!headers.contains_key("x-forwarded-for"));
+        assert!(!headers.contains_key("x-forwarded-proto"));
+        assert!(!headers.contains_key("x-api-token"));
+        assert_eq!(headers["authorization"], "token");
+        assert_eq!(headers["x-env"], "prod");
+        assert_eq!(headers["x-user-id"], "user-42");
+        assert_eq!(headers["x-admin"], "true");
+        // a client value is not kept when the placeholder doesn't resolve
+        assert!(!headers.contains_key("x-tenant"));
+        assert_eq!(headers["accept"], "*/*");
+
+        let rules = parse_header_rules("allow accept; allow x-env*");
+        apply_header_rules(&rules, &mut headers, || unreachable!());
+        assert_eq!(
+            headers.keys().map(HeaderName::as_str).collect::<Vec<_>>(),
+            ["accept", "x-env"]
+        );
+    }
+}
@@ -33,7 +33,8 @@
 //!         let mut adapter = Adapter::new(&options)?;
 //!         
 //!         adapter.register_default_extension();
-//!         adapter.check_init_health().await;
+//!         adapter.start_app_process();
+//!         adapter.check_init_health().await?;
 //!         adapter.run().await
 //!     })
 //! }
@@ -47,14 +48,58 @@
 //! |----------|-------------|---------|
 //! | `AWS_LWA_PORT` | Port your application listens on (falls back to `PORT`) | `8080` |
 //! | `AWS_LWA_HOST` | Host your application binds to | `127.0.0.1` |
+//! | `AWS_LWA_UNIX_SOCKET` | Unix domain socket your application listens on (instead of host/port) | None |
 //! | `AWS_LWA_READINESS_CHECK_PATH` | Health check endpoint path | `/` |
 //! | `AWS_LWA_READINESS_CHECK_PORT` | Health check port | Same as `AWS_LWA_PORT` |
-//! | `AWS_LWA_READINESS_CHECK_PROTOCOL` | Protocol for health checks (`HTTP` or `TCP`) | `HTTP` |
+//! | `AWS_LWA_READINESS_CHECK_PROTOCOL` | Protocol for health checks (`HTTP`, `TCP`, `EXEC` or `FILE`) | `HTTP` |
+//! | `AWS_LWA_READINESS_CHECK_COMMAND` | Command run by `EXEC` readiness checks | None |
+//! | `AWS_LWA_READINESS_CHECK_FILE` | Sentinel file of `FILE` readiness checks | None |
 //! | `AWS_LWA_READINESS_CHECK_HEALTHY_STATUS` | Status codes considered healthy (e.g., `200-399,404`) | `100-499` |
+//! | `AWS_LWA_READINESS_CHECK_METHOD` | HTTP method of the readiness check | `GET` |
+//! | `AWS_LWA_READINESS_CHECK_HEADERS` | `;`-separated `name: value` headers sent with the readiness check | None |
+//! | `AWS_LWA_READINESS_CHECK_BODY` | Condition on the response body (`text`, `regex:...` or `json:/pointer == value`) | None |
+//! | `AWS_LWA_READINESS_CHECKS` | `;`-separated readiness checks replacing the single check above | None |
+//! | `AWS_LWA_READINESS_CHECKS_MODE` | Whether `all` or `any` of the readiness checks must pass | `all` |
+//! | `AWS_LWA_READINESS_CHECK_INTERVAL_MS` | Initial delay between readiness check attempts | `10` |
+//! | `AWS_LWA_READINESS_CHECK_BACKOFF` | Multiplier applied to the delay after each attempt | `1.0` |
+//! | `AWS_LWA_READINESS_CHECK_MAX_INTERVAL_MS` | Maximum delay between readiness check attempts | `1000` |
+//! | `AWS_LWA_READINESS_CHECK_TIMEOUT_MS` | Fail the init if the app is not ready in time | None |
+//! | `AWS_LWA_READINESS_CHECK_LOG_INTERVAL_MS` | Interval of the "app is not ready" logs | `2000` |
+//! | `AWS_LWA_LIVENESS_CHECK_PATH` | Path checked periodically after init to detect a hung app | None |
+//! | `AWS_LWA_LIVENESS_CHECK_INTERVAL_MS` | Interval between liveness checks | `5000` |
+//! | `AWS_LWA_LIVENESS_CHECK_FAILURE_THRESHOLD` | Consecutive failed liveness checks before acting | `3` |
+//! | `AWS_LWA_LIVENESS_CHECK_ACTION` | On liveness failure, `wait` for readiness again or `exit` | `wait` |
 //! | `AWS_LWA_ASYNC_INIT` | Enable async initialization | `false` |
+//! | `AWS_LWA_APP_COMMAND` | Command the adapter spawns and restarts as the web application | None |
+//! | `AWS_LWA_SHUTDOWN_PATH` | Path the adapter POSTs to when the execution environment shuts down | None |
 //! | `AWS_LWA_REMOVE_BASE_PATH` | Base path to strip from requests | None |
+//! | `AWS_LWA_PASS_THROUGH_ROUTES` | Comma-separated `source=path` routes of non-HTTP events, e.g. `sqs=/events/sqs` | None |
+//! | `AWS_LWA_FAN_OUT` | Comma-separated event sources whose batches are sent to the app one record at a time (`sqs`, `kinesis`, `dynamodb`, `s3`) | None |
+//! | `AWS_LWA_S3_OBJECT_ROUTE` | Method and path template of the requests fanned-out S3 objects are sent as | `POST /s3/{bucket}/{key}` |
+//! | `AWS_LWA_FAN_OUT_CONCURRENCY` | Records of a batch sent to the app at the same time | `1` |
+//! | `AWS_LWA_UNWRAP_EVENTS` | Comma-separated event sources whose payload is sent to the app without its envelope (`sns`, `eventbridge`) | None |
+//! | `AWS_LWA_REQUEST_HEADER_RULES` | `;`-separated rules to allow, drop, rename and set request headers | None |
+//! | `AWS_LWA_CONTEXT_HEADERS` | Request context fields added as headers (`claims`, `authorizer`, `source_ip`, `user_agent`, `principal_id` or `all`) | None |
+//! | `AWS_LWA_CONTEXT_HEADER_PREFIX` | Prefix of the request context headers | `x-amzn-` |
+//! | `AWS_LWA_FORWARDED_HEADERS` | Add the client, protocol, host and port as forwarded headers (`off`, `append` or `overwrite`) | `off` |
+//! | `AWS_LWA_FORWARDED_HEADERS_STYLE` | Forwarded headers added: `x-forwarded`, `forwarded` (RFC 7239) or `both` | `x-forwarded` |
 //! | `AWS_LWA_INVOKE_MODE` | Lambda invoke mode (`buffered` or `response_stream`) | `buffered` |
+//! | `AWS_LWA_UPSTREAM_PROTOCOL` | HTTP protocol used toward the app (`http1`, `h2c` or `auto`) | `http1` |
+//! | `AWS_LWA_REQUEST_TIMEOUT_MARGIN_MS` | Bound upstream requests by the Lambda deadline minus this margin | None |
+//! | `AWS_LWA_REQUEST_TIMEOUT_ACTION` | On upstream timeout, return a 504 `response` or a Lambda `error` | `response` |
 //! | `AWS_LWA_ENABLE_COMPRESSION` | Enable response compression (buffered mode only) | `false` |
+//! | `AWS_LWA_METRICS` | Per-invocation metrics: `off` or `emf` (CloudWatch Embedded Metric Format) | `off` |
+//! | `AWS_LWA_ACCESS_LOG` | Access log line per invocation: `off`, `on`, `json` or `clf` | `off` |
+//! | `AWS_LWA_LOG_REDACT_HEADERS` | Comma-separated headers whose values are hidden from logs, besides the defaults | None |
+//! | `AWS_LWA_TRACE_PROPAGATION` | Trace headers added from the Lambda trace (`all`, `xray`, `w3c` or `none`) | `all` |
+//! | `AWS_LWA_TELEMETRY` | Telemetry API subscriber mode (`off`, `tracing` or `app`) | `off` |
+//! | `AWS_LWA_TELEMETRY_PATH` | Path the adapter POSTs telemetry batches to in `app` mode | `/telemetry` |
+//! | `AWS_LWA_TELEMETRY_PORT` | Port the adapter receives Telemetry API deliveries on | `4243` |
+//!
+//! ## OpenTelemetry
+//!
+//! Each invocation is covered by an `invocation` span. With the `otel` cargo feature,
+//! `otel::init_subscriber()` exports these spans over OTLP/HTTP to `AWS_LWA_OTEL_ENDPOINT`.
 //!
 //! ## Response Streaming
 //!
@@ -62,22 +107,86 @@
 //! set `AWS_LWA_INVOKE_MODE=response_stream`. This requires configuring your Lambda function URL
 //! with `InvokeMode: RESPONSE_STREAM`.
 
+mod access_log;
+mod batch;
+mod body;
+mod connector;
+mod context_headers;
+mod envelope;
+mod events;
+mod forwarded;
+mod header_rules;
+mod metrics;
+#[cfg(feature = "otel")]
+pub mod otel;
+mod process;
 mod readiness;
+mod redact;
+mod shutdown;
+mod telemetry;
+mod trace;
+
+pub use body::AdapterBody;
+pub use connector::{AppConnector, AppStream};
+pub use events::EventSource;
+pub use header_rules::HeaderRule;
+pub use readiness::{ReadinessCheck, ReadinessTarget};
 
 // Environment variable names (AWS_LWA_ prefix)
 const ENV_PORT: &str = "AWS_LWA_PORT";
 const ENV_HOST: &str = "AWS_LWA_HOST";
+const ENV_UNIX_SOCKET: &str = "AWS_LWA_UNIX_SOCKET";
 const ENV_READINESS_CHECK_PORT: &str = "AWS_LWA_READINESS_CHECK_PORT";
 const ENV_READINESS_CHECK_PATH: &str = "AWS_LWA_READINESS_CHECK_PATH";
 const ENV_READINESS_CHECK_PROTOCOL: &str = "AWS_LWA_READINESS_CHECK_PROTOCOL";
 const ENV_READINESS_CHECK_HEALTHY_STATUS: &str = "AWS_LWA_READINESS_CHECK_HEALTHY_STATUS";
+const ENV_READINESS_CHECK_COMMAND: &str = "AWS_LWA_READINESS_CHECK_COMMAND";
+const ENV_READINESS_CHECK_FILE: &str = "AWS_LWA_READINESS_CHECK_FILE";
+const ENV_READINESS_CHECK_METHOD: &str = "AWS_LWA_READINESS_CHECK_METHOD";
+const ENV_READINESS_CHECK_HEADERS: &str = "AWS_LWA_READINESS_CHECK_HEADERS";
+const ENV_READINESS_CHECK_BODY: &str = "AWS_LWA_READINESS_CHECK_BODY";
+const ENV_READINESS_CHECKS: &str = "AWS_LWA_READINESS_CHECKS";
+const ENV_READINESS_CHECKS_MODE: &str = "AWS_LWA_READINESS_CHECKS_MODE";
+const ENV_READINESS_CHECK_INTERVAL_MS: &str = "AWS_LWA_READINESS_CHECK_INTERVAL_MS";
+const ENV_READINESS_CHECK_BACKOFF: &str = "AWS_LWA_READINESS_CHECK_BACKOFF";
+const ENV_READINESS_CHECK_MAX_INTERVAL_MS: &str = "AWS_LWA_READINESS_CHECK_MAX_INTERVAL_MS";
+const ENV_READINESS_CHECK_TIMEOUT_MS: &str = "AWS_LWA_READINESS_CHECK_TIMEOUT_MS";
+const ENV_READINESS_CHECK_LOG_INTERVAL_MS: &str = "AWS_LWA_READINESS_CHECK_LOG_INTERVAL_MS";
+const ENV_LIVENESS_CHECK_PATH: &str = "AWS_LWA_LIVENESS_CHECK_PATH";
+const ENV_LIVENESS_CHECK_INTERVAL_MS: &str = "AWS_LWA_LIVENESS_CHECK_INTERVAL_MS";
+const ENV_LIVENESS_CHECK_FAILURE_THRESHOLD: &str = "AWS_LWA_LIVENESS_CHECK_FAILURE_THRESHOLD";
+const ENV_LIVENESS_CHECK_ACTION: &str = "AWS_LWA_LIVENESS_CHECK_ACTION";
 const ENV_REMOVE_BASE_PATH: &str = "AWS_LWA_REMOVE_BASE_PATH";
 const ENV_PASS_THROUGH_PATH: &str = "AWS_LWA_PASS_THROUGH_PATH";
+const ENV_PASS_THROUGH_ROUTES: &str = "AWS_LWA_PASS_THROUGH_ROUTES";
+const ENV_FAN_OUT: &str = "AWS_LWA_FAN_OUT";
+const ENV_FAN_OUT_CONCURRENCY: &str = "AWS_LWA_FAN_OUT_CONCURRENCY";
+const ENV_S3_OBJECT_ROUTE: &str = "AWS_LWA_S3_OBJECT_ROUTE";
+const ENV_UNWRAP_EVENTS: &str = "AWS_LWA_UNWRAP_EVENTS";
 const ENV_ASYNC_INIT: &str = "AWS_LWA_ASYNC_INIT";
+const ENV_APP_COMMAND: &str = "AWS_LWA_APP_COMMAND";
+const ENV_SHUTDOWN_PATH: &str = "AWS_LWA_SHUTDOWN_PATH";
 const ENV_ENABLE_COMPRESSION: &str = "AWS_LWA_ENABLE_COMPRESSION";
 const ENV_INVOKE_MODE: &str = "AWS_LWA_INVOKE_MODE";
+const ENV_UPSTREAM_PROTOCOL: &str = "AWS_LWA_UPSTREAM_PROTOCOL";
+const ENV_REQUEST_TIMEOUT_MARGIN_MS: &str = "AWS_LWA_REQUEST_TIMEOUT_MARGIN_MS";
+const ENV_REQUEST_TIMEOUT_ACTION: &str = "AWS_LWA_REQUEST_TIMEOUT_ACTION";
 const ENV_AUTHORIZATION_SOURCE: &str = "AWS_LWA_AUTHORIZATION_SOURCE";
+const ENV_REQUEST_HEADER_RULES: &str = "AWS_LWA_REQUEST_HEADER_RULES";
+const ENV_CONTEXT_HEADERS: &str = "AWS_LWA_CONTEXT_HEADERS";
+const ENV_CONTEXT_HEADER_PREFIX: &str = "AWS_LWA_CONTEXT_HEADER_PREFIX";
+const ENV_FORWARDED_HEADERS: &str = "AWS_LWA_FORWARDED_HEADERS";
+const ENV_FORWARDED_HEADERS_STYLE: &str = "AWS_LWA_FORWARDED_HEADERS_STYLE";
 const ENV_ERROR_STATUS_CODES: &str = "AWS_LWA_ERROR_STATUS_CODES";
+const ENV_METRICS: &str = "AWS_LWA_METRICS";
+const ENV_ACCESS_LOG: &str = "AWS_LWA_ACCESS_LOG";
+const ENV_LOG_REDACT_HEADERS: &str = "AWS_LWA_LOG_REDACT_HEADERS";
+const ENV_METRICS_NAMESPACE: &str = "AWS_LWA_METRICS_NAMESPACE";
+const ENV_METRICS_DIMENSIONS: &str = "AWS_LWA_METRICS_DIMENSIONS";
+const ENV_TRACE_PROPAGATION: &str = "AWS_LWA_TRACE_PROPAGATION";
+const ENV_TELEMETRY: &str = "AWS_LWA_TELEMETRY";
+const ENV_TELEMETRY_PATH: &str = "AWS_LWA_TELEMETRY_PATH";
+const ENV_TELEMETRY_PORT: &str = "AWS_LWA_TELEMETRY_PORT";
 const ENV_LAMBDA_RUNTIME_API_PROXY: &str = "AWS_LWA_LAMBDA_RUNTIME_API_PROXY";
 
 // Deprecated environment variable names (without prefix)
@@ -92,44 +201,64 @@ const ENV_ASYNC_INIT_DEPRECATED: &str = "ASYNC_INIT";
 // Lambda runtime environment variable
 const ENV_LAMBDA_RUNTIME_API: &str = "AWS_LAMBDA_RUNTIME_API";
 
+/// Whether the next invocation is the first one of this execution environment.
+static COLD_START: AtomicBool = AtomicBool::new(true);
+
+use batch::{Batch, ObjectRoute};
+use bytes::Bytes;
+use context_headers::insert_context_headers;
+use events::parse_pass_through_routes;
+use forwarded::{insert_forwarded_headers, Origin};
+use header_rules::{apply_header_rules, parse_header_rules};
 use http::{
     header::{HeaderName, HeaderValue},
     Method, StatusCode,
 };
 use http_body::Body as HttpBody;
 use http_body_util::BodyExt;
-use hyper::body::Incoming;
-use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::connect::{Connect, HttpConnector};
 use hyper_util::client::legacy::Client;
 use lambda_http::request::RequestContext;
 pub use lambda_http::tracing;
 use lambda_http::Body;
 pub use lambda_http::Error;
 use lambda_http::{Request, RequestExt, Response};
-use readiness::Checkpoint;
+use metrics::Emf;
+use process::AppProcess;
+use readiness::{parse_readiness_checks, Backoff, Checkpoint, ReadinessProbe};
+use redact::Redaction;
+use shutdown::InFlight;
 use std::fmt::Debug;
 use std::{
     env,
     future::Future,
+    path::PathBuf,
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use tokio::{net::TcpStream, time::timeout};
-use tokio_retry::{strategy::FixedInterval, Retry};
+use tokio::{
+    net::{TcpListener, TcpStream, UnixStream},
+    task::JoinSet,
+    time::timeout,
+};
+use tokio_retry::Retry;
 use tower::{Service, ServiceBuilder};
 use tower_http::compression::CompressionLayer;
+use tracing::Instrument;
 use url::Url;
 
 /// Protocol used for readiness checks.
 ///
-/// The adapter supports two protocols for checking if your web application is ready:
+/// The adapter supports these protocols for checking if your web application is ready:
 ///
 /// - [`Protocol::Http`] - Performs an HTTP GET request and checks the response status code
 /// - [`Protocol::Tcp`] - Attempts a TCP connection to verify the port is listening
+/// - [`Protocol::Exec`] - Runs a command and checks that it succeeds
+/// - [`Protocol::File`] - Checks that a sentinel file exists
 ///
 /// # Examples
 ///
@@ -152,6 +281,12 @@ pub enum Protocol {
     /// TCP protocol - only checks if a TCP connection can be established.
     /// Useful for applications that don't have an HTTP health endpoint.
     Tcp,
+    /// Exec protocol - runs `readiness_check_command`, ready when it exits with status 0.
+    /// Useful for workers and apps with custom protocols that ship a CLI probe.
+    Exec,
+    /// File protocol - ready when the file at `readiness_check_file` exists, e.g. `/tmp/ready`.
+    /// Useful for apps that don't listen yet, and signal readiness by creating a file.
+    File,
 }
 
 impl From<&str> for Protocol {
@@ -159,11 +294,46 @@ impl From<&str> for Protocol {
         match value.to_lowercase().as_str() {
             "http" => Protocol::Http,
             "tcp" => Protocol::Tcp,
+            "exec" => Protocol::Exec,
+            "file" => Protocol::File,
             _ => Protocol::Http,
         }
     }
 }
 
+/// How multiple readiness checks are combined.
+///
+/// - [`ReadinessCheckMode::All`] - The app is ready once every check has passed
+/// - [`ReadinessCheckMode::Any`] - The app is ready as soon as one check passes
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::ReadinessCheckMode;
+///
+/// let any: ReadinessCheckMode = "any".into();
+///
+/// assert_eq!(any, ReadinessCheckMode::Any);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReadinessCheckMode {
+    /// All checks must pass. This is the default.
+    #[default]
+    All,
+    /// At least one check must pass.
+    Any,
+}
+
+impl From<&str> for ReadinessCheckMode {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "all" => ReadinessCheckMode::All,
+            "any" => ReadinessCheckMode::Any,
+            _ => ReadinessCheckMode::All,
+        }
+    }
+}
+
 /// Lambda function invoke mode.
 ///
 /// Controls how Lambda handles the response from your function:
@@ -214,6 +384,403 @@ impl From<&str> for LambdaInvokeMode {
     }
 }
 
+/// HTTP protocol used to talk to the web application.
+///
+/// - [`UpstreamProtocol::Http1`] - HTTP/1.1 (the default)
+/// - [`UpstreamProtocol::H2c`] - HTTP/2 over cleartext with prior knowledge
+/// - [`UpstreamProtocol::Auto`] - Detects h2c support during the HTTP readiness check,
+///   falling back to HTTP/1.1
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::UpstreamProtocol;
+///
+/// let h2c: UpstreamProtocol = "h2c".into();
+/// let auto: UpstreamProtocol = "AUTO".into();
+///
+/// assert_eq!(h2c, UpstreamProtocol::H2c);
+/// assert_eq!(auto, UpstreamProtocol::Auto);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UpstreamProtocol {
+    /// HTTP/1.1 - works with any web application. This is the default.
+    #[default]
+    Http1,
+    /// HTTP/2 cleartext with prior knowledge.
+    /// Use this for h2c-only listeners such as gRPC-web servers.
+    H2c,
+    /// Try h2c during the HTTP readiness check and use it if the application
+    /// accepts it, otherwise use HTTP/1.1.
+    Auto,
+}
+
+impl From<&str> for UpstreamProtocol {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "http1" => UpstreamProtocol::Http1,
+            "h2c" => UpstreamProtocol::H2c,
+            "auto" => UpstreamProtocol::Auto,
+            _ => UpstreamProtocol::Http1,
+        }
+    }
+}
+
+/// What the adapter returns when an upstream request exceeds its time budget.
+///
+/// - [`RequestTimeoutAction::Response`] - Returns a `504 Gateway Timeout` response
+/// - [`RequestTimeoutAction::Error`] - Fails the Lambda invocation with an error
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::RequestTimeoutAction;
+///
+/// let response: RequestTimeoutAction = "response".into();
+/// let error: RequestTimeoutAction = "error".into();
+///
+/// assert_eq!(response, RequestTimeoutAction::Response);
+/// assert_eq!(error, RequestTimeoutAction::Error);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RequestTimeoutAction {
+    /// Return a `504 Gateway Timeout` response to the caller. This is the default.
+    #[default]
+    Response,
+    /// Return an error to Lambda, failing the invocation.
+    Error,
+}
+
+impl From<&str> for RequestTimeoutAction {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "response" => RequestTimeoutAction::Response,
+            "error" => RequestTimeoutAction::Error,
+            _ => RequestTimeoutAction::Response,
+        }
+    }
+}
+
+/// What the adapter does when the liveness check keeps failing.
+///
+/// - [`LivenessAction::Wait`] - Wait for the readiness check to pass again before forwarding requests
+/// - [`LivenessAction::Exit`] - Exit the adapter, so Lambda replaces the execution environment
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::LivenessAction;
+///
+/// let exit: LivenessAction = "exit".into();
+///
+/// assert_eq!(exit, LivenessAction::Exit);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LivenessAction {
+    /// Hold the following requests until the app is ready again. This is the default.
+    #[default]
+    Wait,
+    /// Exit the process with a non-zero status.
+    Exit,
+}
+
+impl From<&str> for LivenessAction {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "wait" => LivenessAction::Wait,
+            "exit" => LivenessAction::Exit,
+            _ => LivenessAction::Wait,
+        }
+    }
+}
+
+/// Per-invocation metrics emitted by the adapter.
+///
+/// - [`MetricsMode::Off`] - No metrics
+/// - [`MetricsMode::Emf`] - One CloudWatch Embedded Metric Format (EMF) line per invocation on stdout
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::MetricsMode;
+///
+/// let emf: MetricsMode = "EMF".into();
+///
+/// assert_eq!(emf, MetricsMode::Emf);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MetricsMode {
+    /// No metrics. This is the default.
+    #[default]
+    Off,
+    /// Write an EMF record to stdout after each invocation. CloudWatch Logs
+    /// extracts the metrics from the function's log group.
+    Emf,
+}
+
+impl From<&str> for MetricsMode {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "off" => MetricsMode::Off,
+            "emf" => MetricsMode::Emf,
+            _ => MetricsMode::Off,
+        }
+    }
+}
+
+/// Per-invocation access log written by the adapter.
+///
+/// - [`AccessLog::Off`] - No access log
+/// - [`AccessLog::On`] - JSON lines if `AWS_LAMBDA_LOG_FORMAT` is `JSON`, Common Log Format lines otherwise
+/// - [`AccessLog::Json`] - One JSON line per invocation on stdout
+/// - [`AccessLog::Common`] - One Common Log Format line per invocation on stdout
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::AccessLog;
+///
+/// let clf: AccessLog = "clf".into();
+///
+/// assert_eq!(clf, AccessLog::Common);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AccessLog {
+    /// No access log. This is the default.
+    #[default]
+    Off,
+    /// Follow the function's log format.
+    On,
+    /// JSON lines.
+    Json,
+    /// Common Log Format lines, followed by the request id, event source,
+    /// upstream latency and cold start flag.
+    Common,
+}
+
+impl From<&str> for AccessLog {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "off" | "false" => AccessLog::Off,
+            "on" | "true" => AccessLog::On,
+            "json" => AccessLog::Json,
+            "clf" | "common" => AccessLog::Common,
+            _ => AccessLog::Off,
+        }
+    }
+}
+
+/// Dimension of the metrics emitted in [`MetricsMode::Emf`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MetricDimension {
+    /// `FunctionName`: the Lambda function name.
+    FunctionName,
+    /// `Route`: the API Gateway resource path or HTTP API route, e.g. `/pets/{id}`.
+    Route,
+    /// `StatusClass`: the class of the app's response status, e.g. `2xx`, or `error`.
+    Status,
+}
+
+/// Request context field added to forwarded requests as individual headers.
+///
+/// Header names start with [`AdapterOptions::context_header_prefix`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContextHeader {
+    /// `claim-<name>`: each claim of the Cognito or JWT authorizer.
+    Claims,
+    /// `authorizer-<key>`: each context key of the Lambda authorizer.
+    Authorizer,
+    /// `source-ip`: the client IP address.
+    SourceIp,
+    /// `user-agent`: the client user agent.
+    UserAgent,
+    /// `principal-id`: the Lambda authorizer principal id, or the IAM user ARN.
+    PrincipalId,
+}
+
+/// Whether the adapter adds forwarded headers describing the client to forwarded requests.
+///
+/// - [`ForwardedHeaders::Off`] - Forwards the headers sent by the client as is
+/// - [`ForwardedHeaders::Append`] - Appends the client to `X-Forwarded-For` and `Forwarded`,
+///   and sets `X-Forwarded-Proto`, `-Host` and `-Port` when missing
+/// - [`ForwardedHeaders::Overwrite`] - Replaces all of them
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::ForwardedHeaders;
+///
+/// let append: ForwardedHeaders = "append".into();
+/// let overwrite: ForwardedHeaders = "OVERWRITE".into();
+///
+/// assert_eq!(append, ForwardedHeaders::Append);
+/// assert_eq!(overwrite, ForwardedHeaders::Overwrite);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ForwardedHeaders {
+    /// Do not add forwarded headers. This is the default.
+    #[default]
+    Off,
+    /// Append to the values sent by the client.
+    Append,
+    /// Replace the values sent by the client.
+    Overwrite,
+}
+
+impl From<&str> for ForwardedHeaders {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "off" | "false" => ForwardedHeaders::Off,
+            "append" => ForwardedHeaders::Append,
+            "overwrite" => ForwardedHeaders::Overwrite,
+            _ => ForwardedHeaders::Off,
+        }
+    }
+}
+
+/// Forwarded headers added in [`ForwardedHeaders::Append`] and [`ForwardedHeaders::Overwrite`] modes.
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::ForwardedStyle;
+///
+/// let rfc7239: ForwardedStyle = "forwarded".into();
+/// let both: ForwardedStyle = "BOTH".into();
+///
+/// assert_eq!(rfc7239, ForwardedStyle::Rfc7239);
+/// assert_eq!(both, ForwardedStyle::Both);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ForwardedStyle {
+    /// `X-Forwarded-For`, `X-Forwarded-Proto`, `X-Forwarded-Host` and `X-Forwarded-Port`.
+    /// This is the default.
+    #[default]
+    XForwarded,
+    /// RFC 7239 `Forwarded`.
+    Rfc7239,
+    /// Both.
+    Both,
+}
+
+impl ForwardedStyle {
+    fn x_forwarded(self) -> bool {
+        matches!(self, ForwardedStyle::XForwarded | ForwardedStyle::Both)
+    }
+
+    fn forwarded(self) -> bool {
+        matches!(self, ForwardedStyle::Rfc7239 | ForwardedStyle::Both)
+    }
+}
+
+impl From<&str> for ForwardedStyle {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "x-forwarded" => ForwardedStyle::XForwarded,
+            "forwarded" | "rfc7239" => ForwardedStyle::Rfc7239,
+            "both" => ForwardedStyle::Both,
+            _ => ForwardedStyle::XForwarded,
+        }
+    }
+}
+
+/// Trace headers the adapter adds to forwarded requests from the invocation's X-Ray trace.
+///
+/// - [`TracePropagation::All`] - Adds both `X-Amzn-Trace-Id` and W3C `traceparent`
+/// - [`TracePropagation::Xray`] - Adds `X-Amzn-Trace-Id` only
+/// - [`TracePropagation::W3c`] - Adds `traceparent` only
+/// - [`TracePropagation::None`] - Adds no trace headers
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::TracePropagation;
+///
+/// let w3c: TracePropagation = "w3c".into();
+/// let none: TracePropagation = "NONE".into();
+///
+/// assert_eq!(w3c, TracePropagation::W3c);
+/// assert_eq!(none, TracePropagation::None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TracePropagation {
+    /// Add both `X-Amzn-Trace-Id` and `traceparent`. This is the default.
+    #[default]
+    All,
+    /// Add `X-Amzn-Trace-Id` only.
+    Xray,
+    /// Add `traceparent` only.
+    W3c,
+    /// Do not add trace headers.
+    None,
+}
+
+impl TracePropagation {
+    fn xray(self) -> bool {
+        matches!(self, TracePropagation::All | TracePropagation::Xray)
+    }
+
+    fn w3c(self) -> bool {
+        matches!(self, TracePropagation::All | TracePropagation::W3c)
+    }
+}
+
+impl From<&str> for TracePropagation {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "all" => TracePropagation::All,
+            "xray" => TracePropagation::Xray,
+            "w3c" => TracePropagation::W3c,
+            "none" => TracePropagation::None,
+            _ => TracePropagation::All,
+        }
+    }
+}
+
+/// What the adapter does with events from the Lambda Telemetry API.
+///
+/// - [`TelemetryMode::Off`] - Does not subscribe to the Telemetry API
+/// - [`TelemetryMode::Tracing`] - Re-emits platform events as `tracing` events
+/// - [`TelemetryMode::App`] - Forwards platform events and function logs to the web application
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::TelemetryMode;
+///
+/// let tracing: TelemetryMode = "tracing".into();
+/// let app: TelemetryMode = "APP".into();
+///
+/// assert_eq!(tracing, TelemetryMode::Tracing);
+/// assert_eq!(app, TelemetryMode::App);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TelemetryMode {
+    /// No telemetry subscription. This is the default.
+    #[default]
+    Off,
+    /// Subscribe to platform events (`platform.report`, `platform.runtimeDone`, ...)
+    /// and log them through `tracing` with the `telemetry` target.
+    ///
+    /// Function logs are not subscribed to: the adapter's own output is part of
+    /// them, so re-emitting them would loop.
+    Tracing,
+    /// Subscribe to platform events and function logs, and `POST` each batch as a
+    /// JSON array to the web application's `telemetry_path`.
+    App,
+}
+
+impl From<&str> for TelemetryMode {
+    fn from(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "off" => TelemetryMode::Off,
+            "tracing" => TelemetryMode::Tracing,
+            "app" => TelemetryMode::App,
+            _ => TelemetryMode::Off,
+        }
+    }
+}
+
 /// Configuration options for the Lambda Web Adapter.
 ///
 /// This struct holds all configuration parameters for the adapter. It can be constructed
@@ -227,14 +794,55 @@ impl From<&str> for LambdaInvokeMode {
 /// |-------|---------------------|----------|---------|
 /// | `host` | `AWS_LWA_HOST` | `HOST` | `127.0.0.1` |
 /// | `port` | `AWS_LWA_PORT` | `PORT` | `8080` |
+/// | `unix_socket` | `AWS_LWA_UNIX_SOCKET` | - | None |
 /// | `readiness_check_port` | `AWS_LWA_READINESS_CHECK_PORT` | `READINESS_CHECK_PORT` | Same as `port` |
 /// | `readiness_check_path` | `AWS_LWA_READINESS_CHECK_PATH` | `READINESS_CHECK_PATH` | `/` |
 /// | `readiness_check_protocol` | `AWS_LWA_READINESS_CHECK_PROTOCOL` | `READINESS_CHECK_PROTOCOL` | `HTTP` |
+/// | `readiness_check_command` | `AWS_LWA_READINESS_CHECK_COMMAND` | - | None |
+/// | `readiness_check_file` | `AWS_LWA_READINESS_CHECK_FILE` | - | None |
 /// | `readiness_check_healthy_status` | `AWS_LWA_READINESS_CHECK_HEALTHY_STATUS` | - | `100-499` |
+/// | `readiness_check_method` | `AWS_LWA_READINESS_CHECK_METHOD` | - | `GET` |
+/// | `readiness_check_headers` | `AWS_LWA_READINESS_CHECK_HEADERS` | - | None |
+/// | `readiness_check_body` | `AWS_LWA_READINESS_CHECK_BODY` | - | None |
+/// | `readiness_checks` | `AWS_LWA_READINESS_CHECKS` | - | None |
+/// | `readiness_checks_mode` | `AWS_LWA_READINESS_CHECKS_MODE` | - | `all` |
+/// | `readiness_check_interval_ms` | `AWS_LWA_READINESS_CHECK_INTERVAL_MS` | - | `10` |
+/// | `readiness_check_backoff` | `AWS_LWA_READINESS_CHECK_BACKOFF` | - | `1.0` |
+/// | `readiness_check_max_interval_ms` | `AWS_LWA_READINESS_CHECK_MAX_INTERVAL_MS` | - | `1000` |
+/// | `readiness_check_timeout_ms` | `AWS_LWA_READINESS_CHECK_TIMEOUT_MS` | - | None |
+/// | `readiness_check_log_interval_ms` | `AWS_LWA_READINESS_CHECK_LOG_INTERVAL_MS` | - | `2000` |
+/// | `liveness_check_path` | `AWS_LWA_LIVENESS_CHECK_PATH` | - | None |
+/// | `liveness_check_interval_ms` | `AWS_LWA_LIVENESS_CHECK_INTERVAL_MS` | - | `5000` |
+/// | `liveness_check_failure_threshold` | `AWS_LWA_LIVENESS_CHECK_FAILURE_THRESHOLD` | - | `3` |
+/// | `liveness_check_action` | `AWS_LWA_LIVENESS_CHECK_ACTION` | - | `wait` |
 /// | `base_path` | `AWS_LWA_REMOVE_BASE_PATH` | `REMOVE_BASE_PATH` | None |
+/// | `pass_through_routes` | `AWS_LWA_PASS_THROUGH_ROUTES` | - | None |
+/// | `fan_out` | `AWS_LWA_FAN_OUT` | - | None |
+/// | `fan_out_concurrency` | `AWS_LWA_FAN_OUT_CONCURRENCY` | - | `1` |
+/// | `s3_object_route` | `AWS_LWA_S3_OBJECT_ROUTE` | - | `POST /s3/{bucket}/{key}` |
+/// | `unwrap_events` | `AWS_LWA_UNWRAP_EVENTS` | - | None |
+/// | `request_header_rules` | `AWS_LWA_REQUEST_HEADER_RULES` | - | None |
+/// | `context_headers` | `AWS_LWA_CONTEXT_HEADERS` | - | None |
+/// | `context_header_prefix` | `AWS_LWA_CONTEXT_HEADER_PREFIX` | - | `x-amzn-` |
+/// | `forwarded_headers` | `AWS_LWA_FORWARDED_HEADERS` | - | `off` |
+/// | `forwarded_headers_style` | `AWS_LWA_FORWARDED_HEADERS_STYLE` | - | `x-forwarded` |
 /// | `async_init` | `AWS_LWA_ASYNC_INIT` | `ASYNC_INIT` | `false` |
+/// | `app_command` | `AWS_LWA_APP_COMMAND` | - | None |
+/// | `shutdown_path` | `AWS_LWA_SHUTDOWN_PATH` | - | None |
 /// | `compression` | `AWS_LWA_ENABLE_COMPRESSION` | - | `false` |
 /// | `invoke_mode` | `AWS_LWA_INVOKE_MODE` | - | `buffered` |
+/// | `upstream_protocol` | `AWS_LWA_UPSTREAM_PROTOCOL` | - | `http1` |
+/// | `request_timeout_margin_ms` | `AWS_LWA_REQUEST_TIMEOUT_MARGIN_MS` | - | None |
+/// | `request_timeout_action` | `AWS_LWA_REQUEST_TIMEOUT_ACTION` | - | `response` |
+/// | `metrics` | `AWS_LWA_METRICS` | - | `off` |
+/// | `metrics_namespace` | `AWS_LWA_METRICS_NAMESPACE` | - | `LambdaWebAdapter` |
+/// | `metrics_dimensions` | `AWS_LWA_METRICS_DIMENSIONS` | - | `function_name` |
+/// | `access_log` | `AWS_LWA_ACCESS_LOG` | - | `off` |
+/// | `log_redact_headers` | `AWS_LWA_LOG_REDACT_HEADERS` | - | None |
+/// | `trace_propagation` | `AWS_LWA_TRACE_PROPAGATION` | - | `all` |
+/// | `telemetry` | `AWS_LWA_TELEMETRY` | - | `off` |
+/// | `telemetry_path` | `AWS_LWA_TELEMETRY_PATH` | - | `/telemetry` |
+/// | `telemetry_port` | `AWS_LWA_TELEMETRY_PORT` | - | `4243` |
 ///
 /// # Deprecated Environment Variables
 ///
@@ -269,6 +877,12 @@ pub struct AdapterOptions {
     /// Falls back to `PORT` env var, then default `8080`.
     pub port: String,
 
+    /// Unix domain socket path where the web application is listening.
+    ///
+    /// When set, requests and readiness checks are sent over this socket
+    /// instead of `host:port`, e.g. `/tmp/app.sock`.
+    pub unix_socket: Option<String>,
+
     /// Port to use for readiness checks. Defaults to the same as `port`.
     /// Useful when your application exposes health checks on a different port.
     pub readiness_check_port: String,
@@ -281,6 +895,16 @@ pub struct AdapterOptions {
     /// Default: [`Protocol::Http`]
     pub readiness_check_protocol: Protocol,
 
+    /// Command run by readiness checks with [`Protocol::Exec`], e.g. `pg_isready -q`.
+    /// Required with that protocol.
+    /// Default: None
+    pub readiness_check_command: Option<String>,
+
+    /// Sentinel file checked by readiness checks with [`Protocol::File`], e.g. `/tmp/ready`.
+    /// Required with that protocol.
+    /// Default: None
+    pub readiness_check_file: Option<String>,
+
     /// List of HTTP status codes considered healthy for readiness checks.
     ///
     /// Can be configured via `AWS_LWA_READINESS_CHECK_HEALTHY_STATUS` using:
@@ -291,6 +915,94 @@ pub struct AdapterOptions {
     /// Default: `100-499` (all 1xx, 2xx, 3xx, and 4xx status codes)
     pub readiness_check_healthy_status: Vec<u16>,
 
+    /// HTTP method of the readiness check requests.
+    /// Default: "GET"
+    pub readiness_check_method: String,
+
+    /// Headers sent with the readiness check requests, e.g. an authorization token.
+    ///
+    /// Configured via `AWS_LWA_READINESS_CHECK_HEADERS` as `;`-separated
+    /// `name: value` pairs.
+    ///
+    /// Default: empty
+    pub readiness_check_headers: Vec<(String, String)>,
+
+    /// Condition the body of a readiness check response must meet, in addition to
+    /// the healthy status, for apps that respond before they are warmed up:
+    /// - `regex:<pattern>` - the body matches the regular expression
+    /// - `json:<pointer> == <value>` - the JSON body has the value at the JSON pointer,
+    ///   e.g. `json:/status == "UP"` for Spring Boot Actuator
+    /// - `contains:<text>`, or any other text - the body contains the text
+    ///
+    /// Default: None (any body)
+    pub readiness_check_body: Option<String>,
+
+    /// Readiness checks that replace the single check configured by the
+    /// `readiness_check_*` options, e.g. to also wait for a sidecar process.
+    ///
+    /// Configured via `AWS_LWA_READINESS_CHECKS` as a `;`-separated list of
+    /// `http://`, `tcp://` or `file://` URLs, or `exec:` commands. HTTP checks can be
+    /// followed by a space and their healthy status codes (defaulting to
+    /// `readiness_check_healthy_status`):
+    ///
+    /// `http://127.0.0.1:8080/healthz 200-299; tcp://127.0.0.1:6379; exec:pg_isready -q`
+    ///
+    /// These checks connect over TCP even when `unix_socket` is set, and use HTTP/1.1.
+    ///
+    /// Default: empty (use the single check)
+    pub readiness_checks: Vec<ReadinessCheck>,
+
+    /// Whether all or any of `readiness_checks` must pass.
+    /// Default: [`ReadinessCheckMode::All`]
+    pub readiness_checks_mode: ReadinessCheckMode,
+
+    /// Delay, in milliseconds, before the first retry of a failed readiness check.
+    /// `0` is treated as `1`.
+    /// Default: 10
+    pub readiness_check_interval_ms: u64,
+
+    /// Multiplier applied to the retry delay after each failed readiness check,
+    /// up to `readiness_check_max_interval_ms`. `1.0` keeps a fixed interval.
+    /// Default: 1.0
+    pub readiness_check_backoff: f64,
+
+    /// Maximum delay, in milliseconds, between readiness check retries.
+    /// Default: 1000
+    pub readiness_check_max_interval_ms: u64,
+
+    /// Time, in milliseconds, the app has to become ready during the init phase.
+    ///
+    /// When it is not ready in time, [`Adapter::check_init_health()`] returns an
+    /// error, failing the init instead of waiting until Lambda stops the sandbox.
+    ///
+    /// Default: None (wait until the app is ready)
+    pub readiness_check_timeout_ms: Option<u64>,
+
+    /// Interval, in milliseconds, between the "app is not ready" logs. `0` disables them.
+    /// Default: 2000
+    pub readiness_check_log_interval_ms: u64,
+
+    /// Path checked periodically once the app is ready, e.g. `/health/live`.
+    ///
+    /// The liveness check is an HTTP request to the readiness check port, with the
+    /// same method, headers, healthy status and body condition as the readiness check.
+    /// A check that doesn't respond within `liveness_check_interval_ms` fails.
+    ///
+    /// Default: None (no liveness check)
+    pub liveness_check_path: Option<String>,
+
+    /// Interval, in milliseconds, between liveness checks. `0` is treated as `1`.
+    /// Default: 5000
+    pub liveness_check_interval_ms: u64,
+
+    /// Number of consecutive failed liveness checks after which `liveness_check_action` applies.
+    /// Default: 3
+    pub liveness_check_failure_threshold: u32,
+
+    /// What to do when the liveness check keeps failing.
+    /// Default: [`LivenessAction::Wait`]
+    pub liveness_check_action: LivenessAction,
+
     /// Base path to strip from incoming requests.
     ///
     /// Useful when your Lambda is behind an API Gateway with a stage name
@@ -303,6 +1015,53 @@ pub struct AdapterOptions {
     /// Default: `/events`
     pub pass_through_path: String,
 
+    /// Paths to forward pass-through events to by event source, instead of `pass_through_path`.
+    ///
+    /// Configured via `AWS_LWA_PASS_THROUGH_ROUTES` as a comma-separated list of `source=path`,
+    /// e.g. `sqs=/events/sqs,eventbridge=/events/eb/{detail-type}`. `{field}` placeholders are
+    /// replaced with the string field of the event, or of its first record.
+    ///
+    /// Default: None
+    pub pass_through_routes: Vec<(EventSource, String)>,
+
+    /// Event sources whose batch events are split into records, each sent to the app as
+    /// its own request, instead of forwarding the whole event.
+    ///
+    /// Configured via `AWS_LWA_FAN_OUT` as a comma-separated list. Records the app answers
+    /// with a non-2xx status are returned to Lambda in `batchItemFailures`, which requires
+    /// `ReportBatchItemFailures` on the event source mapping. Supports `sqs`, `kinesis`,
+    /// `dynamodb` and `s3`. S3 objects are sent to [`s3_object_route`](Self::s3_object_route),
+    /// and the invocation fails if any of them fails.
+    ///
+    /// Default: None
+    pub fan_out: Vec<EventSource>,
+
+    /// Maximum number of records of a batch sent to the app at the same time.
+    ///
+    /// Records from SQS FIFO queues, Kinesis and DynamoDB streams are always sent one at
+    /// a time, in order.
+    ///
+    /// Default: `1`
+    pub fan_out_concurrency: usize,
+
+    /// Method and path template of the requests S3 objects are sent as, when `s3` is in
+    /// `fan_out`. `{bucket}` and `{key}` are replaced with the bucket name and the
+    /// URL-decoded object key.
+    ///
+    /// Default: `POST /s3/{bucket}/{key}`
+    pub s3_object_route: String,
+
+    /// Event sources whose payload is sent to the app without the Lambda envelope: the
+    /// SNS `Message`, or the EventBridge `detail`.
+    ///
+    /// Configured via `AWS_LWA_UNWRAP_EVENTS` as a comma-separated list. The envelope
+    /// metadata, such as the topic ARN, message attributes, detail type and source, is
+    /// sent in `x-amzn-sns-*` and `x-amzn-eventbridge-*` headers. Supports `sns` and
+    /// `eventbridge`.
+    ///
+    /// Default: None
+    pub unwrap_events: Vec<EventSource>,
+
     /// Enable async initialization mode.
     ///
     /// When `true`, the adapter will cancel readiness checks after ~9.8 seconds
@@ -312,6 +1071,25 @@ pub struct AdapterOptions {
     /// Default: `false`
     pub async_init: bool,
 
+    /// Command that starts the web application, run through `/bin/sh -c`.
+    ///
+    /// When set, [`Adapter::start_app_process()`] spawns the command, forwards its
+    /// stdout and stderr through `tracing`, and restarts it with backoff whenever it
+    /// exits. Invocations received while the application restarts wait for it to
+    /// pass the readiness check again.
+    ///
+    /// Default: None (the application is started outside the adapter)
+    pub app_command: Option<String>,
+
+    /// Path of an HTTP hook on the web application, called with `POST` when the
+    /// Lambda execution environment shuts down.
+    ///
+    /// The hook is called after in-flight requests have finished, and before a
+    /// supervised application (see `app_command`) receives `SIGTERM`.
+    ///
+    /// Default: None
+    pub shutdown_path: Option<String>,
+
     /// Enable response compression.
     ///
     /// When `true`, responses will be compressed using gzip, deflate, or brotli
@@ -328,6 +1106,10 @@ pub struct AdapterOptions {
     /// Default: [`LambdaInvokeMode::Buffered`]
     pub invoke_mode: LambdaInvokeMode,
 
+    /// HTTP protocol used for requests and HTTP readiness checks to the web application.
+    /// Default: [`UpstreamProtocol::Http1`]
+    pub upstream_protocol: UpstreamProtocol,
+
     /// Header name to copy to the `Authorization` header.
     ///
     /// Useful when your authorization token comes in a custom header
@@ -335,13 +1117,117 @@ pub struct AdapterOptions {
     /// it in the standard `Authorization` header.
     pub authorization_source: Option<String>,
 
-    /// HTTP status codes that should trigger a Lambda error response.
+    /// Rules rewriting the client's request headers before they are forwarded, applied
+    /// after `authorization_source` and before the adapter adds its own headers.
     ///
-    /// When the web application returns one of these status codes,
-    /// the adapter will return an error to Lambda instead of the response.
-    /// This can be useful for triggering Lambda retry behavior.
-    pub error_status_codes: Option<Vec<u16>>,
-}
+    /// Configured via `AWS_LWA_REQUEST_HEADER_RULES` as a `;`-separated list of
+    /// `allow <pattern>`, `drop <pattern>`, `rename <from> <to>` and `set <name>: <value>`
+    /// rules. See [`HeaderRule`].
+    ///
+    /// Default: None
+    pub request_header_rules: Vec<HeaderRule>,
+
+    /// Request context fields added to forwarded requests as individual headers, so the
+    /// app doesn't have to parse `x-amzn-request-context`.
+    ///
+    /// Configured via `AWS_LWA_CONTEXT_HEADERS` as a comma-separated list of `claims`,
+    /// `authorizer`, `source_ip`, `user_agent` and `principal_id`, or `all`. Headers sent
+    /// by the client under the same names are removed.
+    ///
+    /// Default: None
+    pub context_headers: Vec<ContextHeader>,
+
+    /// Prefix of the `context_headers` names, e.g. `x-amzn-claim-sub`.
+    ///
+    /// Default: `x-amzn-`
+    pub context_header_prefix: String,
+
+    /// Whether to add headers describing the client, and the protocol, host and port it
+    /// connected to, from the request context of API Gateway, function URL and ALB events.
+    /// Default: [`ForwardedHeaders::Off`]
+    pub forwarded_headers: ForwardedHeaders,
+
+    /// Forwarded headers added when `forwarded_headers` is not `off`.
+    /// Default: [`ForwardedStyle::XForwarded`]
+    pub forwarded_headers_style: ForwardedStyle,
+
+    /// HTTP status codes that should trigger a Lambda error response.
+    ///
+    /// When the web application returns one of these status codes,
+    /// the adapter will return an error to Lambda instead of the response.
+    /// This can be useful for triggering Lambda retry behavior.
+    pub error_status_codes: Option<Vec<u16>>,
+
+    /// Safety margin, in milliseconds, kept before the Lambda deadline.
+    ///
+    /// When set, each upstream request must return its response headers within
+    /// the remaining invocation time minus this margin. Otherwise the adapter
+    /// gives up and applies `request_timeout_action`, instead of letting a hung
+    /// application consume the whole Lambda timeout.
+    ///
+    /// Default: None (no timeout)
+    pub request_timeout_margin_ms: Option<u64>,
+
+    /// What to return when an upstream request times out.
+    /// Default: [`RequestTimeoutAction::Response`]
+    pub request_timeout_action: RequestTimeoutAction,
+
+    /// Per-invocation metrics: upstream latency, readiness wait time, response bytes,
+    /// cold start and status class.
+    /// Default: [`MetricsMode::Off`]
+    pub metrics: MetricsMode,
+
+    /// CloudWatch namespace of the metrics.
+    /// Default: `LambdaWebAdapter`
+    pub metrics_namespace: String,
+
+    /// Dimensions of the metrics.
+    ///
+    /// Configured via `AWS_LWA_METRICS_DIMENSIONS` as a comma-separated list of
+    /// `function_name`, `route` and `status`. Use an empty value for no dimensions.
+    ///
+    /// Default: `[MetricDimension::FunctionName]`
+    pub metrics_dimensions: Vec<MetricDimension>,
+
+    /// Access log line written after each invocation, with the request id, method,
+    /// path, status, upstream latency, response bytes, event source and cold start flag.
+    /// Default: [`AccessLog::Off`]
+    pub access_log: AccessLog,
+
+    /// Headers whose values are replaced with `[REDACTED]` in the adapter's logs.
+    ///
+    /// Configured via `AWS_LWA_LOG_REDACT_HEADERS` as a comma-separated list. These are
+    /// redacted in addition to `Authorization`, `Proxy-Authorization`, `Cookie`, `Set-Cookie`,
+    /// `X-Api-Key`, `X-Amz-Security-Token`, the `authorization_source` header and the claim
+    /// and authorizer context headers, and the headers `request_header_rules` set from
+    /// the request context or rename a redacted header to. The API key, IAM access key
+    /// and authorizer are also hidden in `x-amzn-request-context`.
+    ///
+    /// Default: None
+    pub log_redact_headers: Vec<String>,
+
+    /// Trace headers added to forwarded requests when the invocation carries an X-Ray trace.
+    ///
+    /// `X-Amzn-Trace-Id` is set to the Lambda trace header, and `traceparent` to its
+    /// W3C translation, so OpenTelemetry SDKs in the application join the Lambda trace.
+    /// Both replace any value sent by the client. A client `tracestate` belongs to the
+    /// replaced `traceparent`, so it is removed whenever `traceparent` is set.
+    ///
+    /// Default: [`TracePropagation::All`]
+    pub trace_propagation: TracePropagation,
+
+    /// Lambda Telemetry API subscriber mode.
+    /// Default: [`TelemetryMode::Off`]
+    pub telemetry: TelemetryMode,
+
+    /// Path to forward Telemetry API batches to in [`TelemetryMode::App`].
+    /// Default: `/telemetry`
+    pub telemetry_path: String,
+
+    /// Port the adapter listens on for Telemetry API deliveries.
+    /// Default: `4243`
+    pub telemetry_port: u16,
+}
 
 /// Helper to get env var with deprecation warning for old name
 fn get_env_with_deprecation(new_name: &str, old_name: &str, default: &str) -> String {
@@ -392,11 +1278,56 @@ impl Default for AdapterOptions {
         AdapterOptions {
             host: get_env_with_deprecation(ENV_HOST, ENV_HOST_DEPRECATED, "127.0.0.1"),
             port: port.clone(),
+            unix_socket: env::var(ENV_UNIX_SOCKET).ok(),
             readiness_check_port: get_env_with_deprecation(
                 ENV_READINESS_CHECK_PORT,
                 ENV_READINESS_CHECK_PORT_DEPRECATED,
                 &port,
             ),
+            readiness_check_method: env::var(ENV_READINESS_CHECK_METHOD).unwrap_or_else(|_| "GET".to_string()),
+            readiness_check_headers: env::var(ENV_READINESS_CHECK_HEADERS)
+                .map(|headers| parse_headers(&headers))
+                .unwrap_or_default(),
+            readiness_check_body: env::var(ENV_READINESS_CHECK_BODY).ok(),
+            readiness_checks: env::var(ENV_READINESS_CHECKS)
+                .map(|checks| parse_readiness_checks(&checks, &readiness_check_healthy_status))
+                .unwrap_or_default(),
+            readiness_checks_mode: env::var(ENV_READINESS_CHECKS_MODE)
+                .unwrap_or_else(|_| "all".to_string())
+                .as_str()
+                .into(),
+            readiness_check_interval_ms: env::var(ENV_READINESS_CHECK_INTERVAL_MS)
+                .ok()
+                .and_then(|interval| interval.parse().ok())
+                .unwrap_or(10),
+            readiness_check_backoff: env::var(ENV_READINESS_CHECK_BACKOFF)
+                .ok()
+                .and_then(|backoff| backoff.parse().ok())
+                .unwrap_or(1.0),
+            readiness_check_max_interval_ms: env::var(ENV_READINESS_CHECK_MAX_INTERVAL_MS)
+                .ok()
+                .and_then(|interval| interval.parse().ok())
+                .unwrap_or(1000),
+            readiness_check_timeout_ms: env::var(ENV_READINESS_CHECK_TIMEOUT_MS)
+                .ok()
+                .and_then(|timeout| timeout.parse().ok()),
+            readiness_check_log_interval_ms: env::var(ENV_READINESS_CHECK_LOG_INTERVAL_MS)
+                .ok()
+                .and_then(|interval| interval.parse().ok())
+                .unwrap_or(2000),
+            liveness_check_path: env::var(ENV_LIVENESS_CHECK_PATH).ok(),
+            liveness_check_interval_ms: env::var(ENV_LIVENESS_CHECK_INTERVAL_MS)
+                .ok()
+                .and_then(|interval| interval.parse().ok())
+                .unwrap_or(5000),
+            liveness_check_failure_threshold: env::var(ENV_LIVENESS_CHECK_FAILURE_THRESHOLD)
+                .ok()
+                .and_then(|threshold| threshold.parse().ok())
+                .unwrap_or(3),
+            liveness_check_action: env::var(ENV_LIVENESS_CHECK_ACTION)
+                .unwrap_or_else(|_| "wait".to_string())
+                .as_str()
+                .into(),
             readiness_check_healthy_status,
             readiness_check_path: get_env_with_deprecation(
                 ENV_READINESS_CHECK_PATH,
@@ -410,11 +1341,29 @@ impl Default for AdapterOptions {
             )
             .as_str()
             .into(),
+            readiness_check_command: env::var(ENV_READINESS_CHECK_COMMAND).ok(),
+            readiness_check_file: env::var(ENV_READINESS_CHECK_FILE).ok(),
             base_path: get_optional_env_with_deprecation(ENV_REMOVE_BASE_PATH, ENV_REMOVE_BASE_PATH_DEPRECATED),
             pass_through_path: env::var(ENV_PASS_THROUGH_PATH).unwrap_or_else(|_| "/events".to_string()),
+            pass_through_routes: env::var(ENV_PASS_THROUGH_ROUTES)
+                .map(|routes| parse_pass_through_routes(&routes))
+                .unwrap_or_default(),
+            fan_out: env::var(ENV_FAN_OUT)
+                .map(|sources| parse_event_sources(&sources, &Batch::SOURCES, "fan-out"))
+                .unwrap_or_default(),
+            fan_out_concurrency: env::var(ENV_FAN_OUT_CONCURRENCY)
+                .ok()
+                .and_then(|concurrency| concurrency.parse().ok())
+                .unwrap_or(1),
+            s3_object_route: env::var(ENV_S3_OBJECT_ROUTE).unwrap_or_else(|_| "POST /s3/{bucket}/{key}".to_string()),
+            unwrap_events: env::var(ENV_UNWRAP_EVENTS)
+                .map(|sources| parse_event_sources(&sources, &envelope::SOURCES, "unwrap"))
+                .unwrap_or_default(),
             async_init: get_env_with_deprecation(ENV_ASYNC_INIT, ENV_ASYNC_INIT_DEPRECATED, "false")
                 .parse()
                 .unwrap_or(false),
+            app_command: env::var(ENV_APP_COMMAND).ok(),
+            shutdown_path: env::var(ENV_SHUTDOWN_PATH).ok(),
             compression: env::var(ENV_ENABLE_COMPRESSION)
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
@@ -423,10 +1372,71 @@ impl Default for AdapterOptions {
                 .unwrap_or_else(|_| "buffered".to_string())
                 .as_str()
                 .into(),
+            upstream_protocol: env::var(ENV_UPSTREAM_PROTOCOL)
+                .unwrap_or_else(|_| "http1".to_string())
+                .as_str()
+                .into(),
             authorization_source: env::var(ENV_AUTHORIZATION_SOURCE).ok(),
+            request_header_rules: env::var(ENV_REQUEST_HEADER_RULES)
+                .map(|rules| parse_header_rules(&rules))
+                .unwrap_or_default(),
+            context_headers: env::var(ENV_CONTEXT_HEADERS)
+                .map(|fields| parse_context_headers(&fields))
+                .unwrap_or_default(),
+            context_header_prefix: env::var(ENV_CONTEXT_HEADER_PREFIX).unwrap_or_else(|_| "x-amzn-".to_string()),
+            forwarded_headers: env::var(ENV_FORWARDED_HEADERS)
+                .unwrap_or_else(|_| "off".to_string())
+                .as_str()
+                .into(),
+            forwarded_headers_style: env::var(ENV_FORWARDED_HEADERS_STYLE)
+                .unwrap_or_else(|_| "x-forwarded".to_string())
+                .as_str()
+                .into(),
             error_status_codes: env::var(ENV_ERROR_STATUS_CODES)
                 .ok()
                 .map(|codes| parse_status_codes(&codes)),
+            request_timeout_margin_ms: env::var(ENV_REQUEST_TIMEOUT_MARGIN_MS)
+                .ok()
+                .and_then(|margin| margin.parse().ok()),
+            request_timeout_action: env::var(ENV_REQUEST_TIMEOUT_ACTION)
+                .unwrap_or_else(|_| "response".to_string())
+                .as_str()
+                .into(),
+            metrics: env::var(ENV_METRICS)
+                .unwrap_or_else(|_| "off".to_string())
+                .as_str()
+                .into(),
+            metrics_namespace: env::var(ENV_METRICS_NAMESPACE).unwrap_or_else(|_| "LambdaWebAdapter".to_string()),
+            metrics_dimensions: env::var(ENV_METRICS_DIMENSIONS)
+                .map(|dimensions| parse_metric_dimensions(&dimensions))
+                .unwrap_or_else(|_| vec![MetricDimension::FunctionName]),
+            access_log: env::var(ENV_ACCESS_LOG)
+                .unwrap_or_else(|_| "off".to_string())
+                .as_str()
+                .into(),
+            log_redact_headers: env::var(ENV_LOG_REDACT_HEADERS)
+                .map(|headers| {
+                    headers
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|header| !header.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            trace_propagation: env::var(ENV_TRACE_PROPAGATION)
+                .unwrap_or_else(|_| "all".to_string())
+                .as_str()
+                .into(),
+            telemetry: env::var(ENV_TELEMETRY)
+                .unwrap_or_else(|_| "off".to_string())
+                .as_str()
+                .into(),
+            telemetry_path: env::var(ENV_TELEMETRY_PATH).unwrap_or_else(|_| "/telemetry".to_string()),
+            telemetry_port: env::var(ENV_TELEMETRY_PORT)
+                .ok()
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(4243),
         }
     }
 }
@@ -468,6 +1478,92 @@ fn parse_status_codes(input: &str) -> Vec<u16> {
         .collect()
 }
 
+/// Parses a comma-separated list of context header fields, e.g. `"claims,authorizer,source_ip"`.
+///
+/// Fields are `claims`, `authorizer`, `source_ip`, `user_agent` and `principal_id`, or
+/// `all` for every field. Unknown fields are logged as warnings and skipped.
+fn parse_context_headers(input: &str) -> Vec<ContextHeader> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .flat_map(|field| match field.to_lowercase().as_str() {
+            "all" => vec![
+                ContextHeader::Claims,
+                ContextHeader::Authorizer,
+                ContextHeader::SourceIp,
+                ContextHeader::UserAgent,
+                ContextHeader::PrincipalId,
+            ],
+            "claims" => vec![ContextHeader::Claims],
+            "authorizer" => vec![ContextHeader::Authorizer],
+            "source_ip" => vec![ContextHeader::SourceIp],
+            "user_agent" => vec![ContextHeader::UserAgent],
+            "principal_id" => vec![ContextHeader::PrincipalId],
+            _ => {
+                tracing::warn!("Unknown context header: {}", field);
+                vec![]
+            }
+        })
+        .collect()
+}
+
+/// Parses a comma-separated list of event sources for `option`, e.g. `"sqs,kinesis"`.
+///
+/// Sources that are unknown or not in `supported` are logged as warnings and skipped.
+fn parse_event_sources(input: &str, supported: &[EventSource], option: &str) -> Vec<EventSource> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|source| !source.is_empty())
+        .filter_map(|name| match EventSource::from_name(&name.to_lowercase()) {
+            Some(source) if supported.contains(&source) => Some(source),
+            _ => {
+                tracing::warn!("Unsupported {} event source: {}", option, name);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parses a comma-separated list of metric dimensions, e.g. `"function_name,route,status"`.
+///
+/// Unknown dimensions are logged as warnings and skipped.
+fn parse_metric_dimensions(input: &str) -> Vec<MetricDimension> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|dimension| !dimension.is_empty())
+        .filter_map(|dimension| match dimension.to_lowercase().as_str() {
+            "function_name" => Some(MetricDimension::FunctionName),
+            "route" => Some(MetricDimension::Route),
+            "status" => Some(MetricDimension::Status),
+            _ => {
+                tracing::warn!("Unknown metric dimension: {}", dimension);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parses a `;`-separated list of `name: value` headers, e.g. `"Authorization: Bearer x; X-Probe: 1"`.
+///
+/// Entries without a `:` are logged as warnings and skipped.
+fn parse_headers(input: &str) -> Vec<(String, String)> {
+    input
+        .split(';')
+        .map(str::trim)
+        .filter(|header| !header.is_empty())
+        .filter_map(|header| match header.split_once(':') {
+            Some((name, value)) => Some((name.trim().to_string(), value.trim().to_string())),
+            None => {
+                tracing::warn!("Failed to parse header: {}", header);
+                None
+            }
+        })
+        .collect()
+}
+
 /// The Lambda Web Adapter.
 ///
 /// This is the main struct that handles forwarding Lambda events to your web application.
@@ -475,15 +1571,16 @@ fn parse_status_codes(input: &str) -> Vec<u16> {
 ///
 /// # Type Parameters
 ///
-/// - `C` - The HTTP connector type (typically [`hyper_util::client::legacy::connect::HttpConnector`])
+/// - `C` - The HTTP connector type (typically [`AppConnector`])
 /// - `B` - The request body type (typically [`lambda_http::Body`])
 ///
 /// # Lifecycle
 ///
 /// 1. Create an adapter with [`Adapter::new()`]
 /// 2. Register as a Lambda extension with [`Adapter::register_default_extension()`]
-/// 3. Wait for the web app to be ready with [`Adapter::check_init_health()`]
-/// 4. Start processing events with [`Adapter::run()`]
+/// 3. Optionally start the web app with [`Adapter::start_app_process()`]
+/// 4. Wait for the web app to be ready with [`Adapter::check_init_health()`]
+/// 5. Start processing events with [`Adapter::run()`]
 ///
 /// # Examples
 ///
@@ -495,28 +1592,64 @@ fn parse_status_codes(input: &str) -> Vec<u16> {
 /// let mut adapter = Adapter::new(&options)?;
 ///
 /// adapter.register_default_extension();
-/// adapter.check_init_health().await;
+/// adapter.start_app_process();
+/// adapter.check_init_health().await?;
 /// adapter.run().await
 /// # }
 /// ```
 #[derive(Clone)]
 pub struct Adapter<C, B> {
     client: Arc<Client<C, B>>,
-    healthcheck_url: Url,
-    healthcheck_protocol: Protocol,
-    healthcheck_healthy_status: Vec<u16>,
+    h2c_client: Option<Arc<Client<C, B>>>,
+    h2c_detected: Arc<AtomicBool>,
+    readiness_checks: Vec<ReadinessCheck>,
+    readiness_checks_mode: ReadinessCheckMode,
+    /// Client for `AWS_LWA_READINESS_CHECKS`, which may target other processes than
+    /// the web application. `None` when the single check through `client` is used.
+    readiness_client: Option<Client<HttpConnector, Body>>,
+    readiness_probe: Arc<ReadinessProbe>,
+    readiness_backoff: Backoff,
+    readiness_timeout: Option<Duration>,
+    readiness_log_interval: Duration,
+    liveness_check: Option<ReadinessCheck>,
+    liveness_interval: Duration,
+    liveness_failure_threshold: u32,
+    liveness_action: LivenessAction,
     async_init: bool,
     ready_at_init: Arc<AtomicBool>,
+    app_process: Option<Arc<AppProcess>>,
+    shutdown_path: Option<String>,
+    in_flight: Arc<InFlight>,
     domain: Url,
+    unix_socket: Option<PathBuf>,
     base_path: Option<String>,
     pass_through_path: String,
+    pass_through_routes: Vec<(EventSource, String)>,
+    fan_out: Vec<EventSource>,
+    fan_out_concurrency: usize,
+    s3_object_route: ObjectRoute,
+    unwrap_events: Vec<EventSource>,
     compression: bool,
     invoke_mode: LambdaInvokeMode,
     authorization_source: Option<String>,
+    request_header_rules: Vec<HeaderRule>,
+    context_headers: Vec<ContextHeader>,
+    context_header_prefix: String,
+    forwarded_headers: ForwardedHeaders,
+    forwarded_headers_style: ForwardedStyle,
     error_status_codes: Option<Vec<u16>>,
+    request_timeout_margin: Option<Duration>,
+    request_timeout_action: RequestTimeoutAction,
+    emf: Option<Arc<Emf>>,
+    access_log: Option<access_log::Format>,
+    redaction: Arc<Redaction>,
+    trace_propagation: TracePropagation,
+    telemetry: TelemetryMode,
+    telemetry_path: String,
+    telemetry_port: u16,
 }
 
-impl Adapter<HttpConnector, Body> {
+impl Adapter<AppConnector, Body> {
     /// Creates a new HTTP Adapter instance.
     ///
     /// This function initializes a new HTTP client configured to communicate with
@@ -527,6 +1660,11 @@ impl Adapter<HttpConnector, Body> {
     /// connections. Otherwise, a 4-second idle timeout is used for connection
     /// pooling.
     ///
+    /// If `unix_socket` is set, the client connects to the web application over
+    /// that Unix domain socket instead of TCP. With `upstream_protocol` set to
+    /// [`UpstreamProtocol::H2c`] the client speaks HTTP/2 with prior knowledge; with
+    /// [`UpstreamProtocol::Auto`] an additional h2c client is kept for detection.
+    ///
     /// # Arguments
     ///
     /// * `options` - Configuration options for the adapter
@@ -549,7 +1687,7 @@ impl Adapter<HttpConnector, Body> {
     /// let options = AdapterOptions::default();
     /// let adapter = Adapter::new(&options).expect("Failed to create adapter");
     /// ```
-    pub fn new(options: &AdapterOptions) -> Result<Adapter<HttpConnector, Body>, Error> {
+    pub fn new(options: &AdapterOptions) -> Result<Adapter<AppConnector, Body>, Error> {
         let mut builder = Client::builder(hyper_util::rt::TokioExecutor::new());
 
         // When running under SnapStart, CLOCK_MONOTONIC can be inconsistent after
@@ -562,7 +1700,15 @@ impl Adapter<HttpConnector, Body> {
             builder.pool_idle_timeout(Duration::from_secs(4));
         }
 
-        let client = builder.build(HttpConnector::new());
+        let connector = AppConnector::new(options.unix_socket.as_deref());
+        let (client, h2c_client) = match options.upstream_protocol {
+            UpstreamProtocol::Http1 => (builder.build(connector), None),
+            UpstreamProtocol::H2c => (builder.http2_only(true).build(connector), None),
+            UpstreamProtocol::Auto => {
+                let client = builder.build(connector.clone());
+                (client, Some(Arc::new(builder.http2_only(true).build(connector))))
+            }
+        };
 
         let schema = "http";
 
@@ -578,6 +1724,19 @@ impl Adapter<HttpConnector, Body> {
             ))
         })?;
 
+        let liveness_check = match &options.liveness_check_path {
+            Some(path) => Some(ReadinessCheck {
+                target: ReadinessTarget::Http(healthcheck_url.join(path).map_err(|e| {
+                    Error::from(format!(
+                        "Invalid liveness check path configuration (path={}): {}",
+                        path, e
+                    ))
+                })?),
+                healthy_status: options.readiness_check_healthy_status.clone(),
+            }),
+            None => None,
+        };
+
         let domain: Url = format!("{}://{}:{}", schema, options.host, options.port)
             .parse()
             .map_err(|e| {
@@ -587,15 +1746,48 @@ impl Adapter<HttpConnector, Body> {
                 ))
             })?;
 
-        // Validate TCP protocol requirements
-        if options.readiness_check_protocol == Protocol::Tcp {
-            if healthcheck_url.host().is_none() {
-                return Err(Error::from("TCP readiness check requires a valid host in the URL"));
-            }
-            if healthcheck_url.port().is_none() {
-                return Err(Error::from("TCP readiness check requires a port in the URL"));
-            }
-        }
+        let (readiness_checks, readiness_client) = if options.readiness_checks.is_empty() {
+            let target = match options.readiness_check_protocol {
+                Protocol::Http => ReadinessTarget::Http(healthcheck_url),
+                // Validate TCP protocol requirements
+                Protocol::Tcp => {
+                    let Some(host) = healthcheck_url.host_str() else {
+                        return Err(Error::from("TCP readiness check requires a valid host in the URL"));
+                    };
+                    let Some(port) = healthcheck_url.port() else {
+                        return Err(Error::from("TCP readiness check requires a port in the URL"));
+                    };
+                    ReadinessTarget::Tcp {
+                        host: host.to_string(),
+                        port,
+                    }
+                }
+                Protocol::Exec => match &options.readiness_check_command {
+                    Some(command) => ReadinessTarget::Exec(command.clone()),
+                    None => {
+                        return Err(Error::from(
+                            "Exec readiness check requires a command in AWS_LWA_READINESS_CHECK_COMMAND",
+                        ))
+                    }
+                },
+                Protocol::File => match &options.readiness_check_file {
+                    Some(path) => ReadinessTarget::File(PathBuf::from(path)),
+                    None => {
+                        return Err(Error::from(
+                            "File readiness check requires a path in AWS_LWA_READINESS_CHECK_FILE",
+                        ))
+                    }
+                },
+            };
+            let check = ReadinessCheck {
+                target,
+                healthy_status: options.readiness_check_healthy_status.clone(),
+            };
+            (vec![check], None)
+        } else {
+            let readiness_client = Client::builder(hyper_util::rt::TokioExecutor::new()).build(HttpConnector::new());
+            (options.readiness_checks.clone(), Some(readiness_client))
+        };
 
         let compression = if options.compression && options.invoke_mode == LambdaInvokeMode::ResponseStream {
             tracing::warn!("Compression is not supported with response streaming. Disabling compression.");
@@ -606,23 +1798,135 @@ impl Adapter<HttpConnector, Body> {
 
         Ok(Adapter {
             client: Arc::new(client),
-            healthcheck_url,
-            healthcheck_protocol: options.readiness_check_protocol,
-            healthcheck_healthy_status: options.readiness_check_healthy_status.clone(),
+            h2c_client,
+            h2c_detected: Arc::new(AtomicBool::new(false)),
+            readiness_checks,
+            readiness_checks_mode: options.readiness_checks_mode,
+            readiness_client,
+            readiness_probe: Arc::new(ReadinessProbe::new(
+                &options.readiness_check_method,
+                &options.readiness_check_headers,
+                options.readiness_check_body.as_deref(),
+            )?),
+            readiness_backoff: Backoff::new(
+                Duration::from_millis(options.readiness_check_interval_ms),
+                options.readiness_check_backoff,
+                Duration::from_millis(options.readiness_check_max_interval_ms),
+            ),
+            readiness_timeout: options.readiness_check_timeout_ms.map(Duration::from_millis),
+            readiness_log_interval: Duration::from_millis(options.readiness_check_log_interval_ms),
+            liveness_check,
+            liveness_interval: Duration::from_millis(options.liveness_check_interval_ms.max(1)),
+            liveness_failure_threshold: options.liveness_check_failure_threshold.max(1),
+            liveness_action: options.liveness_check_action,
             domain,
+            unix_socket: options.unix_socket.as_ref().map(PathBuf::from),
             base_path: options.base_path.clone(),
             pass_through_path: options.pass_through_path.clone(),
+            pass_through_routes: options.pass_through_routes.clone(),
+            fan_out: options.fan_out.clone(),
+            fan_out_concurrency: options.fan_out_concurrency.max(1),
+            s3_object_route: ObjectRoute::new(&options.s3_object_route),
+            unwrap_events: options.unwrap_events.clone(),
             async_init: options.async_init,
             ready_at_init: Arc::new(AtomicBool::new(false)),
+            app_process: options
+                .app_command
+                .as_deref()
+                .map(|command| Arc::new(AppProcess::new(command))),
+            shutdown_path: options.shutdown_path.clone(),
+            in_flight: Arc::new(InFlight::new()),
             compression,
             invoke_mode: options.invoke_mode,
             authorization_source: options.authorization_source.clone(),
+            request_header_rules: options.request_header_rules.clone(),
+            context_headers: options.context_headers.clone(),
+            context_header_prefix: options.context_header_prefix.to_lowercase(),
+            forwarded_headers: options.forwarded_headers,
+            forwarded_headers_style: options.forwarded_headers_style,
             error_status_codes: options.error_status_codes.clone(),
+            request_timeout_margin: options.request_timeout_margin_ms.map(Duration::from_millis),
+            request_timeout_action: options.request_timeout_action,
+            emf: (options.metrics == MetricsMode::Emf)
+                .then(|| Arc::new(Emf::new(&options.metrics_namespace, &options.metrics_dimensions))),
+            access_log: match options.access_log {
+                AccessLog::Off => None,
+                AccessLog::On => match env::var("AWS_LAMBDA_LOG_FORMAT") {
+                    Ok(log_format) if log_format.eq_ignore_ascii_case("json") => Some(access_log::Format::Json),
+                    _ => Some(access_log::Format::Common),
+                },
+                AccessLog::Json => Some(access_log::Format::Json),
+                AccessLog::Common => Some(access_log::Format::Common),
+            },
+            redaction: Arc::new(
+                Redaction::new(
+                    options
+                        .log_redact_headers
+                        .iter()
+                        .chain(&options.authorization_source)
+                        .map(String::as_str),
+                    &options.context_header_prefix.to_lowercase(),
+                )
+                .with_header_rules(&options.request_header_rules),
+            ),
+            trace_propagation: options.trace_propagation,
+            telemetry: options.telemetry,
+            telemetry_path: options.telemetry_path.clone(),
+            telemetry_port: options.telemetry_port,
         })
     }
+
+    /// Applies runtime API proxy configuration from environment variables.
+    ///
+    /// If `AWS_LWA_LAMBDA_RUNTIME_API_PROXY` is set, this method overwrites
+    /// `AWS_LAMBDA_RUNTIME_API` to redirect Lambda runtime calls through the proxy.
+    ///
+    /// # Important
+    ///
+    /// This method **must** be called before starting the tokio runtime to avoid
+    /// race conditions with environment variable modification in a multi-threaded context.
+    ///
+    /// # Safety Note
+    ///
+    /// This function uses `std::env::set_var` which modifies process-wide state.
+    /// In future Rust versions, this will be marked `unsafe` due to potential race
+    /// conditions. Calling this before spawning any threads ensures safety.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use lambda_web_adapter::Adapter;
+    ///
+    /// fn main() {
+    ///     // Call before starting tokio runtime
+    ///     Adapter::apply_runtime_proxy_config();
+    ///
+    ///     let runtime = tokio::runtime::Builder::new_multi_thread()
+    ///         .enable_all()
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     runtime.block_on(async {
+    ///         // ... adapter setup and run
+    ///     });
+    /// }
+    /// ```
+    pub fn apply_runtime_proxy_config() {
+        if let Ok(runtime_proxy) = env::var(ENV_LAMBDA_RUNTIME_API_PROXY) {
+            // We need to overwrite the env variable because lambda_http::run()
+            // calls lambda_runtime::run() which doesn't allow changing the client URI.
+            //
+            // This is safe here because it's called before the tokio runtime starts,
+            // ensuring no other threads exist yet.
+            env::set_var(ENV_LAMBDA_RUNTIME_API, runtime_proxy);
+        }
+    }
 }
 
-impl Adapter<HttpConnector, Body> {
+impl<C> Adapter<C, Body>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
     /// Registers the adapter as a Lambda extension.
     ///
     /// Lambda extensions are loaded before the function handler and can perform
@@ -633,34 +1937,44 @@ impl Adapter<HttpConnector, Body> {
     /// fails, the process will exit with code 1 to signal Lambda that initialization
     /// failed.
     ///
+    /// The extension subscribes to the `SHUTDOWN` event. When it arrives, the adapter
+    /// shuts down gracefully (see [`Adapter::shutdown()`]) and the process exits.
+    /// Unless `telemetry` is [`TelemetryMode::Off`], the extension also subscribes to
+    /// the Telemetry API; a failed subscription is logged and does not stop the adapter.
+    ///
     /// # Panics
     ///
     /// This method spawns a background task that will call `std::process::exit(1)`
-    /// if extension registration fails, terminating the Lambda execution environment.
+    /// if extension registration fails, terminating the Lambda execution environment,
+    /// and `std::process::exit(0)` once shutdown has completed.
     pub fn register_default_extension(&self) {
+        let adapter = self.clone();
+        let aws_lambda_runtime_api: String =
+            env::var(ENV_LAMBDA_RUNTIME_API).unwrap_or_else(|_| "127.0.0.1:9001".to_string());
+
         // register as an external extension
         tokio::task::spawn(async move {
-            if let Err(e) = Self::register_extension_internal().await {
+            if let Err(e) = adapter.register_extension_internal(&aws_lambda_runtime_api).await {
                 tracing::error!(error = %e, "Extension registration failed - terminating process");
                 std::process::exit(1);
             }
+            std::process::exit(0);
         });
     }
 
     /// Internal implementation of extension registration.
     ///
-    /// Registers with the Lambda Extensions API and waits for the next event.
-    /// This keeps the extension alive for the duration of the Lambda instance.
-    async fn register_extension_internal() -> Result<(), Error> {
-        let aws_lambda_runtime_api: String =
-            env::var(ENV_LAMBDA_RUNTIME_API).unwrap_or_else(|_| "127.0.0.1:9001".to_string());
+    /// Registers with the Lambda Extensions API for the `SHUTDOWN` event and waits
+    /// for it. This keeps the extension alive for the duration of the Lambda instance.
+    /// Returns once the adapter has shut down.
+    async fn register_extension_internal(&self, aws_lambda_runtime_api: &str) -> Result<(), Error> {
         let client = Client::builder(hyper_util::rt::TokioExecutor::new()).build(HttpConnector::new());
 
         let register_req = hyper::Request::builder()
             .method(Method::POST)
             .uri(format!("http://{aws_lambda_runtime_api}/2020-01-01/extension/register"))
             .header("Lambda-Extension-Name", "lambda-adapter")
-            .body(Body::from("{ \"events\": [] }"))?;
+            .body(Body::from("{ \"events\": [\"SHUTDOWN\"] }"))?;
 
         let register_res = client.request(register_req).await?;
 
@@ -676,19 +1990,182 @@ impl Adapter<HttpConnector, Body> {
             .get("Lambda-Extension-Identifier")
             .ok_or_else(|| Error::from("Missing Lambda-Extension-Identifier header"))?;
 
-        let next_req = hyper::Request::builder()
-            .method(Method::GET)
-            .uri(format!(
-                "http://{aws_lambda_runtime_api}/2020-01-01/extension/event/next"
-            ))
-            .header("Lambda-Extension-Identifier", extension_id)
-            .body(Body::Empty)?;
+        if let Err(e) = self
+            .start_telemetry(&client, aws_lambda_runtime_api, extension_id)
+            .await
+        {
+            tracing::error!(error = %e, "Telemetry API subscription failed");
+        }
+
+        loop {
+            let next_req = hyper::Request::builder()
+                .method(Method::GET)
+                .uri(format!(
+                    "http://{aws_lambda_runtime_api}/2020-01-01/extension/event/next"
+                ))
+                .header("Lambda-Extension-Identifier", extension_id)
+                .body(Body::Empty)?;
+
+            let next_res = client.request(next_req).await?;
+            let event_bytes = next_res.into_body().collect().await?.to_bytes();
+            let event: serde_json::Value = serde_json::from_slice(&event_bytes)?;
+
+            if event["eventType"] == "SHUTDOWN" {
+                let reason = event["shutdownReason"].as_str().unwrap_or("unknown");
+                let deadline_ms = event["deadlineMs"].as_u64().unwrap_or_default();
+                self.shutdown(reason, UNIX_EPOCH + Duration::from_millis(deadline_ms))
+                    .await;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Starts the telemetry listener and subscribes to the Telemetry API, according to `telemetry`.
+    async fn start_telemetry(
+        &self,
+        client: &Client<HttpConnector, Body>,
+        aws_lambda_runtime_api: &str,
+        extension_id: &HeaderValue,
+    ) -> Result<(), Error> {
+        let types: &[&str] = match self.telemetry {
+            TelemetryMode::Off => return Ok(()),
+            TelemetryMode::Tracing => &["platform"],
+            TelemetryMode::App => &["platform", "function"],
+        };
 
-        client.request(next_req).await?;
+        let listener = TcpListener::bind(("0.0.0.0", self.telemetry_port)).await?;
+        match self.telemetry {
+            TelemetryMode::App => {
+                let adapter = self.clone();
+                telemetry::serve(listener, move |batch| {
+                    let adapter = adapter.clone();
+                    async move { adapter.forward_telemetry(batch).await }
+                });
+            }
+            _ => telemetry::serve(listener, |batch| async move { telemetry::trace_batch(&batch) }),
+        }
 
+        telemetry::subscribe(client, aws_lambda_runtime_api, extension_id, types, self.telemetry_port).await?;
+        tracing::info!(?types, port = self.telemetry_port, "subscribed to the Telemetry API");
         Ok(())
     }
 
+    /// Forwards a batch of Telemetry API events to the web application's `telemetry_path`.
+    ///
+    /// Batches the application cannot accept, e.g. while it is still starting, are dropped.
+    async fn forward_telemetry(&self, batch: Bytes) {
+        let mut telemetry_url = self.domain.clone();
+        telemetry_url.set_path(&self.telemetry_path);
+        let telemetry_req = hyper::Request::builder()
+            .method(Method::POST)
+            .uri(telemetry_url.as_str())
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(batch.to_vec()));
+
+        let result = match telemetry_req {
+            Ok(req) => self.upstream_client().request(req).await.map_err(Error::from),
+            Err(e) => Err(Error::from(e)),
+        };
+        match result {
+            Ok(res) if !res.status().is_success() => {
+                tracing::warn!(status = %res.status(), "app rejected telemetry batch");
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, "failed to forward telemetry batch to app"),
+        }
+    }
+
+    /// Shuts the adapter down gracefully before the given deadline.
+    ///
+    /// This method:
+    /// 1. Stops accepting new invocations
+    /// 2. Waits for in-flight invocations to finish
+    /// 3. Calls the `shutdown_path` hook on the web application, if configured
+    /// 4. Sends `SIGTERM` to the supervised application process, if any, and waits for it to exit
+    ///
+    /// Each step is bounded by the deadline. Steps that do not finish in time are logged and skipped.
+    pub async fn shutdown(&self, reason: &str, deadline: SystemTime) {
+        let remaining = || deadline.duration_since(SystemTime::now()).unwrap_or_default();
+
+        tracing::info!(
+            reason,
+            deadline_ms = deadline.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+            in_flight = self.in_flight.count(),
+            "shutting down"
+        );
+
+        self.in_flight.close();
+        if timeout(remaining(), self.in_flight.drained()).await.is_err() {
+            tracing::warn!(
+                in_flight = self.in_flight.count(),
+                "in-flight requests did not finish before the shutdown deadline"
+            );
+        }
+
+        if let Some(shutdown_path) = self.shutdown_path.as_deref() {
+            let mut hook_url = self.domain.clone();
+            hook_url.set_path(shutdown_path);
+            let hook_req = hyper::Request::builder()
+                .method(Method::POST)
+                .uri(hook_url.as_str())
+                .body(Body::Empty);
+            match hook_req {
+                Ok(hook_req) => match timeout(remaining(), self.upstream_client().request(hook_req)).await {
+                    Ok(Ok(response)) => tracing::info!(status = %response.status(), "called app shutdown hook"),
+                    Ok(Err(e)) => tracing::warn!(error = %e, "app shutdown hook failed"),
+                    Err(_) => tracing::warn!("app shutdown hook did not respond before the shutdown deadline"),
+                },
+                Err(e) => tracing::warn!(error = %e, "invalid app shutdown hook request"),
+            }
+        }
+
+        if let Some(app_process) = &self.app_process {
+            app_process.terminate();
+            if timeout(remaining(), app_process.exited()).await.is_err() {
+                tracing::warn!("app process did not exit before the shutdown deadline");
+            }
+        }
+
+        #[cfg(feature = "otel")]
+        if timeout(remaining(), tokio::task::spawn_blocking(otel::flush))
+            .await
+            .is_err()
+        {
+            tracing::warn!("spans were not exported before the shutdown deadline");
+        }
+
+        tracing::info!("shutdown complete");
+    }
+
+    /// Starts the web application configured with `app_command`, if any.
+    ///
+    /// The application runs under a background supervisor task: its output is
+    /// forwarded through `tracing`, and when it exits it is restarted with
+    /// exponential backoff while the adapter is marked not ready. This method
+    /// does nothing when no `app_command` is configured.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use lambda_web_adapter::{Adapter, AdapterOptions};
+    ///
+    /// # async fn example() -> Result<(), lambda_web_adapter::Error> {
+    /// let options = AdapterOptions {
+    ///     app_command: Some("gunicorn -b 127.0.0.1:8080 app:app".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let mut adapter = Adapter::new(&options)?;
+    /// adapter.start_app_process();
+    /// adapter.check_init_health().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_app_process(&self) {
+        if let Some(app_process) = &self.app_process {
+            app_process.clone().supervise(self.ready_at_init.clone());
+        }
+    }
+
     /// Checks if the web application is ready during Lambda initialization.
     ///
     /// This method performs readiness checks against your web application using
@@ -704,6 +2181,12 @@ impl Adapter<HttpConnector, Body> {
     /// The first request will re-check readiness if the application wasn't ready
     /// during initialization.
     ///
+    /// # Errors
+    ///
+    /// Returns an error if `readiness_check_timeout_ms` is set and the application
+    /// is not ready within it. With `async_init`, this only happens when the timeout
+    /// is shorter than the async init period.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -712,52 +2195,178 @@ impl Adapter<HttpConnector, Body> {
     /// # async fn example() -> Result<(), lambda_web_adapter::Error> {
     /// let options = AdapterOptions::default();
     /// let mut adapter = Adapter::new(&options)?;
-    /// adapter.check_init_health().await;
+    /// adapter.check_init_health().await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn check_init_health(&mut self) {
-        let ready_at_init = if self.async_init {
-            timeout(Duration::from_secs_f32(9.8), self.check_readiness())
-                .await
-                .unwrap_or_default()
+    pub async fn check_init_health(&mut self) -> Result<(), Error> {
+        // with async init, stop waiting before Lambda's 10s init timeout
+        let init_timeout = if self.async_init {
+            let async_init_timeout = Duration::from_secs_f32(9.8);
+            Some(
+                self.readiness_timeout
+                    .map_or(async_init_timeout, |t| t.min(async_init_timeout)),
+            )
         } else {
-            self.check_readiness().await
+            self.readiness_timeout
         };
+        let ready_at_init = match init_timeout {
+            Some(init_timeout) => timeout(init_timeout, self.check_readiness()).await.unwrap_or_default(),
+            None => self.check_readiness().await,
+        };
+
+        if let (false, Some(readiness_timeout)) = (ready_at_init, self.readiness_timeout) {
+            if init_timeout == Some(readiness_timeout) {
+                let checks = self
+                    .readiness_checks
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(Error::from(format!(
+                    "app is not ready after {}ms, readiness checks: {}",
+                    readiness_timeout.as_millis(),
+                    checks
+                )));
+            }
+        }
+
         self.ready_at_init.store(ready_at_init, Ordering::SeqCst);
+        self.start_liveness_monitor();
+        Ok(())
     }
 
-    /// Performs a single readiness check against the configured endpoint.
+    /// Spawns a task that checks the app periodically, if `liveness_check_path` is set.
+    ///
+    /// After `liveness_check_failure_threshold` consecutive failures, the app is marked
+    /// as not ready, so the next request waits for the readiness check to pass again,
+    /// or the adapter exits with [`LivenessAction::Exit`]. Lambda freezes the execution
+    /// environment between invocations, so checks only run while it is active.
+    fn start_liveness_monitor(&self) {
+        let Some(check) = self.liveness_check.clone() else {
+            return;
+        };
+
+        let adapter = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(adapter.liveness_interval);
+            // don't catch up on the ticks missed while the environment was frozen
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval.tick().await;
+
+            let mut failures = 0;
+            loop {
+                interval.tick().await;
+                // the app is starting or recovering, the readiness check is in charge
+                if !adapter.ready_at_init.load(Ordering::SeqCst) {
+                    failures = 0;
+                    continue;
+                }
+
+                let alive = timeout(adapter.liveness_interval, adapter.check_web_readiness(&check))
+                    .await
+                    .is_ok_and(|result| result.is_ok());
+                if alive {
+                    failures = 0;
+                    continue;
+                }
+
+                failures += 1;
+                tracing::warn!(url = %check, failures, "liveness check failed");
+                if failures < adapter.liveness_failure_threshold {
+                    continue;
+                }
+
+                match adapter.liveness_action {
+                    LivenessAction::Wait => {
+                        tracing::error!("app is not alive, waiting for it to be ready again");
+                        adapter.ready_at_init.store(false, Ordering::SeqCst);
+                        failures = 0;
+                    }
+                    LivenessAction::Exit => {
+                        tracing::error!("app is not alive, exiting");
+                        std::process::exit(1);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Waits until the configured readiness checks pass.
     async fn check_readiness(&self) -> bool {
-        let url = self.healthcheck_url.clone();
-        let protocol = self.healthcheck_protocol;
-        self.is_web_ready(&url, &protocol).await
+        self.is_web_ready().await
     }
 
     /// Waits for the web application to become ready, with retries.
     ///
-    /// Uses a fixed 10ms interval between retry attempts and logs progress
-    /// at increasing intervals (100ms, 500ms, 1s, 2s, 5s, 10s).
-    async fn is_web_ready(&self, url: &Url, protocol: &Protocol) -> bool {
-        let mut checkpoint = Checkpoint::new();
-        Retry::spawn(FixedInterval::from_millis(10), || {
-            if checkpoint.lapsed() {
-                tracing::info!(url = %url.to_string(), "app is not ready after {}ms", checkpoint.next_ms());
-                checkpoint.increment();
+    /// Each readiness check is retried concurrently, following the configured backoff,
+    /// until all of them (or any of them, with [`ReadinessCheckMode::Any`]) have passed.
+    /// Progress is logged every `readiness_check_log_interval_ms`, unless it is `0`, with the
+    /// state of each check.
+    async fn is_web_ready(&self) -> bool {
+        let mut checks = JoinSet::new();
+        for index in 0..self.readiness_checks.len() {
+            let adapter = self.clone();
+            checks.spawn(async move {
+                let check = &adapter.readiness_checks[index];
+                let result = Retry::spawn(adapter.readiness_backoff, || adapter.check_web_readiness(check)).await;
+                (index, result.is_ok())
+            });
+        }
+
+        let mut passed = vec![false; self.readiness_checks.len()];
+        let log_progress = !self.readiness_log_interval.is_zero();
+        let mut checkpoint = Checkpoint::new(self.readiness_log_interval.as_millis());
+        loop {
+            let passed_count = passed.iter().filter(|passed| **passed).count();
+            let ready = match self.readiness_checks_mode {
+                ReadinessCheckMode::All => passed_count == passed.len(),
+                ReadinessCheckMode::Any => passed_count > 0 || passed.is_empty(),
+            };
+            if ready {
+                return true;
             }
-            self.check_web_readiness(url, protocol)
-        })
-        .await
-        .is_ok()
+            if checks.is_empty() {
+                return false;
+            }
+
+            tokio::select! {
+                Some(joined) = checks.join_next() => {
+                    // a check that panicked has not passed
+                    if let Ok((index, ok)) = joined {
+                        passed[index] = ok;
+                    }
+                }
+                _ = tokio::time::sleep_until(checkpoint.deadline().into()), if log_progress => {
+                    let progress = self
+                        .readiness_checks
+                        .iter()
+                        .zip(passed.iter())
+                        .map(|(check, passed)| {
+                            let state = if *passed { "ready" } else { "not ready" };
+                            format!("{check} ({state})")
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    tracing::info!(checks = %progress, "app is not ready after {}ms", checkpoint.next_ms());
+                    checkpoint.increment();
+                }
+            }
+        }
     }
 
     /// Performs a single readiness check using the configured protocol.
     ///
     /// For HTTP: Makes a GET request and checks if the status code is in the healthy range.
-    /// For TCP: Attempts to establish a TCP connection.
-    async fn check_web_readiness(&self, url: &Url, protocol: &Protocol) -> Result<(), i8> {
-        match protocol {
-            Protocol::Http => {
+    /// In [`UpstreamProtocol::Auto`] mode, the request is tried over h2c first; if the app
+    /// accepts it, h2c is used for all further requests.
+    /// For TCP: Attempts to establish a TCP connection, or a Unix domain socket connection
+    /// when `unix_socket` is configured.
+    /// For Exec: Runs the command and checks that it exits with status 0.
+    /// For File: Checks that the file exists.
+    async fn check_web_readiness(&self, check: &ReadinessCheck) -> Result<(), i8> {
+        match &check.target {
+            ReadinessTarget::Http(url) => {
                 // url is already validated in Adapter::new(), this conversion should always succeed
                 // If it fails, it indicates a programming error, not a runtime condition
                 let uri: http::Uri = url
@@ -765,32 +2374,59 @@ impl Adapter<HttpConnector, Body> {
                     .parse()
                     .expect("BUG: healthcheck_url should be valid - validated in Adapter::new()");
 
-                match self.client.get(uri).await {
-                    Ok(response) if self.healthcheck_healthy_status.contains(&response.status().as_u16()) => {
-                        tracing::debug!("app is ready");
-                        Ok(())
-                    }
-                    _ => {
-                        tracing::trace!("app is not ready");
-                        Err(-1)
+                let request = || self.readiness_probe.request(uri.clone());
+
+                let response = if let Some(client) = &self.readiness_client {
+                    client.request(request()).await
+                } else {
+                    match self.h2c_client.as_deref() {
+                        Some(h2c_client) if !self.h2c_detected.load(Ordering::SeqCst) => {
+                            match h2c_client.request(request()).await {
+                                Ok(response) => {
+                                    tracing::info!("app accepts h2c, using HTTP/2 for upstream requests");
+                                    self.h2c_detected.store(true, Ordering::SeqCst);
+                                    Ok(response)
+                                }
+                                Err(_) => self.client.request(request()).await,
+                            }
+                        }
+                        _ => self.upstream_client().request(request()).await,
                     }
+                };
+
+                let ready = match response {
+                    Ok(response) => self.readiness_probe.is_healthy(response, &check.healthy_status).await,
+                    Err(_) => false,
+                };
+                if ready {
+                    tracing::debug!("app is ready");
+                    Ok(())
+                } else {
+                    tracing::trace!("app is not ready");
+                    Err(-1)
                 }
             }
-            Protocol::Tcp => {
-                // url is already validated in Adapter::new(), host and port should exist
-                // If they don't, it indicates a programming error, not a runtime condition
-                let host = url
-                    .host_str()
-                    .expect("BUG: healthcheck_url should have host - validated in Adapter::new()");
-                let port = url
-                    .port()
-                    .expect("BUG: healthcheck_url should have port - validated in Adapter::new()");
-
-                match TcpStream::connect(format!("{}:{}", host, port)).await {
+            ReadinessTarget::Tcp { host, port } => {
+                if let (Some(unix_socket), None) = (self.unix_socket.as_deref(), &self.readiness_client) {
+                    return match UnixStream::connect(unix_socket).await {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(-1),
+                    };
+                }
+
+                match TcpStream::connect((host.as_str(), *port)).await {
                     Ok(_) => Ok(()),
                     Err(_) => Err(-1),
                 }
             }
+            ReadinessTarget::Exec(command) => match readiness::exec_ready(command, readiness::EXEC_TIMEOUT).await {
+                true => Ok(()),
+                false => Err(-1),
+            },
+            ReadinessTarget::File(path) => match path.exists() {
+                true => Ok(()),
+                false => Err(-1),
+            },
         }
     }
 
@@ -831,84 +2467,99 @@ impl Adapter<HttpConnector, Body> {
         }
     }
 
-    /// Applies runtime API proxy configuration from environment variables.
-    ///
-    /// If `AWS_LWA_LAMBDA_RUNTIME_API_PROXY` is set, this method overwrites
-    /// `AWS_LAMBDA_RUNTIME_API` to redirect Lambda runtime calls through the proxy.
-    ///
-    /// # Important
-    ///
-    /// This method **must** be called before starting the tokio runtime to avoid
-    /// race conditions with environment variable modification in a multi-threaded context.
-    ///
-    /// # Safety Note
-    ///
-    /// This function uses `std::env::set_var` which modifies process-wide state.
-    /// In future Rust versions, this will be marked `unsafe` due to potential race
-    /// conditions. Calling this before spawning any threads ensures safety.
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// use lambda_web_adapter::Adapter;
-    ///
-    /// fn main() {
-    ///     // Call before starting tokio runtime
-    ///     Adapter::apply_runtime_proxy_config();
-    ///
-    ///     let runtime = tokio::runtime::Builder::new_multi_thread()
-    ///         .enable_all()
-    ///         .build()
-    ///         .unwrap();
-    ///
-    ///     runtime.block_on(async {
-    ///         // ... adapter setup and run
-    ///     });
-    /// }
-    /// ```
-    pub fn apply_runtime_proxy_config() {
-        if let Ok(runtime_proxy) = env::var(ENV_LAMBDA_RUNTIME_API_PROXY) {
-            // We need to overwrite the env variable because lambda_http::run()
-            // calls lambda_runtime::run() which doesn't allow changing the client URI.
-            //
-            // This is safe here because it's called before the tokio runtime starts,
-            // ensuring no other threads exist yet.
-            env::set_var(ENV_LAMBDA_RUNTIME_API, runtime_proxy);
+    /// Returns the client for requests to the web application, which is the
+    /// h2c client once [`UpstreamProtocol::Auto`] has detected h2c support.
+    fn upstream_client(&self) -> &Client<C, Body> {
+        match self.h2c_client.as_deref() {
+            Some(h2c_client) if self.h2c_detected.load(Ordering::SeqCst) => h2c_client,
+            _ => &self.client,
         }
     }
 
     /// Forwards a Lambda event to the web application and returns the response.
     ///
     /// This method:
-    /// 1. Checks readiness if async_init is enabled and app wasn't ready at init
+    /// 1. Checks readiness if async_init is enabled and app wasn't ready at init,
+    ///    or if the supervised app process has been restarted
     /// 2. Transforms the Lambda event into an HTTP request
-    /// 3. Adds Lambda context headers (`x-amzn-request-context`, `x-amzn-lambda-context`)
+    /// 3. Adds Lambda context headers (`x-amzn-request-context`, `x-amzn-lambda-context`,
+    ///    `x-amzn-deadline-ms`, `x-amzn-remaining-time-ms`)
     /// 4. Strips the base path if configured
-    /// 5. Forwards the request to the web application
+    /// 5. Forwards the request to the web application, bounded by the Lambda deadline
+    ///    if `request_timeout_margin` is configured
     /// 6. Returns the response (or error if status code is in error_status_codes)
-    async fn fetch_response(&self, event: Request) -> Result<Response<Incoming>, Error> {
-        if self.async_init && !self.ready_at_init.load(Ordering::SeqCst) {
-            self.is_web_ready(&self.healthcheck_url, &self.healthcheck_protocol)
+    async fn fetch_response(
+        &self,
+        event: Request,
+        metrics: &mut Option<metrics::Invocation>,
+    ) -> Result<Response<AdapterBody>, Error> {
+        if (self.async_init || self.app_process.is_some() || self.liveness_check.is_some())
+            && !self.ready_at_init.load(Ordering::SeqCst)
+        {
+            let started = Instant::now();
+            self.is_web_ready()
+                .instrument(tracing::info_span!("readiness_wait"))
                 .await;
             self.ready_at_init.store(true, Ordering::SeqCst);
+            if let Some(metrics) = metrics {
+                metrics.readiness_wait = started.elapsed();
+            }
         }
 
+        let decode_span = tracing::info_span!("decode_event").entered();
+
         let request_context = event.request_context();
         let lambda_context = event.lambda_context();
         let path = event.raw_http_path().to_string();
         let mut path = path.as_str();
-        let (parts, body) = event.into_parts();
+        let (parts, mut body) = event.into_parts();
 
         // strip away Base Path if environment variable REMOVE_BASE_PATH is set.
         if let Some(base_path) = self.base_path.as_deref() {
             path = path.trim_start_matches(base_path);
         }
 
+        let mut event_source = None;
+        let mut batch = None;
+        let mut envelope_headers = None;
+        let routed_path;
         if matches!(request_context, RequestContext::PassThrough) && parts.method == Method::POST {
-            path = self.pass_through_path.as_str();
+            let payload: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            let source = EventSource::detect(&payload);
+            event_source = Some(source);
+            if self.fan_out.contains(&source) {
+                batch = Batch::new(source, &payload, &self.s3_object_route);
+            }
+            if self.unwrap_events.contains(&source) {
+                if let Some(unwrapped) = envelope::unwrap(source, &payload) {
+                    envelope_headers = Some(unwrapped.headers);
+                    body = Body::Binary(unwrapped.body);
+                }
+            }
+            routed_path = events::route(&self.pass_through_routes, source, &payload);
+            path = routed_path.as_deref().unwrap_or(self.pass_through_path.as_str());
+        }
+
+        if let Some(metrics) = metrics {
+            metrics.path = path.to_string();
         }
 
         let mut req_headers = parts.headers;
+        // ALB describes the client in headers the rules may drop
+        let origin = Origin::new(&request_context, &req_headers);
+
+        // the header rules apply to the client's headers, before the adapter adds its own
+        if let Some(authorization_source) = self.authorization_source.as_deref() {
+            if let Some(original) = req_headers.remove(authorization_source) {
+                req_headers.insert("authorization", original);
+            } else {
+                tracing::warn!("\"{}\" header not found in request headers", authorization_source);
+            }
+        }
+
+        apply_header_rules(&self.request_header_rules, &mut req_headers, || {
+            serde_json::to_value(&request_context).unwrap_or_default()
+        });
 
         // include request context in http header "x-amzn-request-context"
         req_headers.insert(
@@ -916,12 +2567,65 @@ impl Adapter<HttpConnector, Body> {
             HeaderValue::from_bytes(serde_json::to_string(&request_context)?.as_bytes())?,
         );
 
+        // include the detected source of non-HTTP events in http header "x-amzn-event-source"
+        if let Some(event_source) = event_source {
+            req_headers.insert(
+                HeaderName::from_static("x-amzn-event-source"),
+                HeaderValue::from_static(event_source.as_str()),
+            );
+        }
+
+        // describe the envelope of an unwrapped event payload, e.g. "x-amzn-sns-topic-arn"
+        if let Some(envelope_headers) = envelope_headers {
+            req_headers.remove(http::header::CONTENT_LENGTH);
+            req_headers.extend(envelope_headers);
+        }
+
         // include lambda context in http header "x-amzn-lambda-context"
         req_headers.insert(
             HeaderName::from_static("x-amzn-lambda-context"),
             HeaderValue::from_bytes(serde_json::to_string(&lambda_context)?.as_bytes())?,
         );
 
+        // include the invocation deadline and the time left until it, so the app can budget its work
+        let remaining = lambda_context
+            .deadline()
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+        req_headers.insert(
+            HeaderName::from_static("x-amzn-deadline-ms"),
+            HeaderValue::from(lambda_context.deadline),
+        );
+        req_headers.insert(
+            HeaderName::from_static("x-amzn-remaining-time-ms"),
+            HeaderValue::from(remaining.as_millis() as u64),
+        );
+
+        // join the Lambda trace: X-Ray header as is, and translated to W3C trace context
+        if let Some(xray_trace_id) = lambda_context.xray_trace_id.as_deref() {
+            if self.trace_propagation.xray() {
+                match HeaderValue::from_str(xray_trace_id) {
+                    Ok(value) => {
+                        req_headers.insert(HeaderName::from_static("x-amzn-trace-id"), value);
+                    }
+                    Err(_) => tracing::warn!(xray_trace_id, "X-Ray trace id is not a valid header value, skipping"),
+                }
+            }
+            if self.trace_propagation.w3c() {
+                match trace::traceparent(xray_trace_id) {
+                    Some(traceparent) => {
+                        // the client's tracestate describes the trace being replaced
+                        req_headers.remove(HeaderName::from_static("tracestate"));
+                        req_headers.insert(
+                            HeaderName::from_static("traceparent"),
+                            HeaderValue::from_str(&traceparent)?,
+                        );
+                    }
+                    None => tracing::debug!(xray_trace_id, "X-Ray trace id cannot be translated to traceparent"),
+                }
+            }
+        }
+
         // Multi-tenancy support: propagate tenant_id from Lambda context
         if let Some(ref tenant_id) = lambda_context.tenant_id {
             if let Ok(value) = HeaderValue::from_str(tenant_id) {
@@ -932,19 +2636,52 @@ impl Adapter<HttpConnector, Body> {
             }
         }
 
-        if let Some(authorization_source) = self.authorization_source.as_deref() {
-            if let Some(original) = req_headers.remove(authorization_source) {
-                req_headers.insert("authorization", original);
-            } else {
-                tracing::warn!("\"{}\" header not found in request headers", authorization_source);
-            }
-        }
+        insert_forwarded_headers(
+            self.forwarded_headers,
+            self.forwarded_headers_style,
+            &origin,
+            &mut req_headers,
+        );
+
+        insert_context_headers(
+            &self.context_headers,
+            &self.context_header_prefix,
+            &request_context,
+            &mut req_headers,
+        );
 
         let mut app_url = self.domain.clone();
         app_url.set_path(path);
         app_url.set_query(parts.uri.query().filter(|q| !q.is_empty()));
 
-        tracing::debug!(app_url = %app_url, req_headers = ?req_headers, "sending request to app server");
+        tracing::debug!(app_url = %app_url, req_headers = ?self.redaction.headers(&req_headers), "sending request to app server");
+
+        // send the records of a batch one by one, and report the ones the app failed to process;
+        // for S3, fail the invocation if any object failed, so Lambda retries it
+        if let Some(batch) = batch {
+            decode_span.exit();
+            let deadline = self
+                .request_timeout_margin
+                .map(|margin| tokio::time::Instant::now() + remaining.saturating_sub(margin));
+            let upstream_started = Instant::now();
+            let response = batch::send_batch(
+                self.upstream_client(),
+                &app_url,
+                &req_headers,
+                batch,
+                self.fan_out_concurrency,
+                deadline,
+            )
+            .await;
+            if let Some(metrics) = metrics {
+                metrics.upstream_latency = upstream_started.elapsed();
+            }
+            let response = response?;
+            return Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "application/json")
+                .body(AdapterBody::full(response.to_string()))?);
+        }
 
         let mut builder = hyper::Request::builder().method(parts.method).uri(app_url.to_string());
         if let Some(headers) = builder.headers_mut() {
@@ -959,75 +2696,439 @@ impl Adapter<HttpConnector, Body> {
             // Body is marked #[non_exhaustive], handle future variants
             _ => body.to_vec(),
         };
-        let request = builder.body(Body::Binary(body_bytes))?;
+        #[cfg_attr(not(feature = "otel"), allow(unused_mut))]
+        let mut request = builder.body(Body::Binary(body_bytes))?;
+        decode_span.exit();
+
+        let upstream_span = tracing::info_span!(
+            "upstream_request",
+            otel.kind = "client",
+            http.request.method = %request.method(),
+            url.path = request.uri().path(),
+            http.response.status_code = tracing::field::Empty,
+        );
+        // continue the Lambda trace from the upstream_request span instead
+        #[cfg(feature = "otel")]
+        if self.trace_propagation.w3c() && lambda_context.xray_trace_id.is_some() {
+            otel::inject_traceparent(&upstream_span, request.headers_mut());
+        }
+
+        let upstream_started = Instant::now();
+        let upstream_request = self
+            .upstream_client()
+            .request(request)
+            .instrument(upstream_span.clone());
+        let mut app_response = match self.request_timeout_margin {
+            Some(margin) => {
+                let budget = remaining.saturating_sub(margin);
+                match timeout(budget, upstream_request).await {
+                    Ok(response) => response?,
+                    Err(_) => return self.request_timeout_response(&lambda_context.request_id, budget, margin),
+                }
+            }
+            None => upstream_request.await?,
+        };
+        upstream_span.record("http.response.status_code", app_response.status().as_u16());
+        if let Some(metrics) = metrics {
+            metrics.upstream_latency = upstream_started.elapsed();
+        }
+
+        // Check if status code should trigger an error
+        if let Some(error_codes) = &self.error_status_codes {
+            let status = app_response.status().as_u16();
+            if error_codes.contains(&status) {
+                let body_bytes = app_response
+                    .into_body()
+                    .collect()
+                    .await
+                    .map(|c| c.to_bytes())
+                    .unwrap_or_default();
+                let body_str = String::from_utf8_lossy(&body_bytes);
+                return Err(Error::from(format!(
+                    "{{\"statusCode\":{},\"body\":{}}}",
+                    status,
+                    serde_json::to_string(&*body_str).unwrap_or_else(|_| format!("\"{}\"", body_str))
+                )));
+            }
+        }
+
+        // remove "transfer-encoding" from the response to support "sam local start-api"
+        app_response.headers_mut().remove("transfer-encoding");
+
+        tracing::debug!(status = %app_response.status(), body_size = ?app_response.body().size_hint().lower(),
+            app_headers = ?self.redaction.headers(app_response.headers()), "responding to lambda event");
+
+        Ok(app_response.map(AdapterBody::from))
+    }
+
+    /// Builds the result for an upstream request that exceeded its time budget,
+    /// according to the configured [`RequestTimeoutAction`].
+    fn request_timeout_response(
+        &self,
+        request_id: &str,
+        budget: Duration,
+        margin: Duration,
+    ) -> Result<Response<AdapterBody>, Error> {
+        tracing::error!(
+            request_id = %request_id,
+            timeout_ms = budget.as_millis() as u64,
+            margin_ms = margin.as_millis() as u64,
+            action = ?self.request_timeout_action,
+            "upstream request timed out"
+        );
+
+        match self.request_timeout_action {
+            RequestTimeoutAction::Response => Ok(Response::builder()
+                .status(StatusCode::GATEWAY_TIMEOUT)
+                .header("content-type", "application/json")
+                .body(AdapterBody::full(r#"{"message":"Gateway Timeout"}"#))?),
+            RequestTimeoutAction::Error => Err(Error::from(format!(
+                "upstream request timed out after {}ms",
+                budget.as_millis()
+            ))),
+        }
+    }
+}
+
+/// Implementation of [`tower::Service`] for the adapter.
+///
+/// This allows the adapter to be used directly with the Lambda runtime,
+/// which expects a `Service` that can handle Lambda events.
+impl<C> Service<Request> for Adapter<C, Body>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    type Response = Response<AdapterBody>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut core::task::Context<'_>) -> core::task::Poll<Result<(), Self::Error>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, event: Request) -> Self::Future {
+        let adapter = self.clone();
+        let cold_start = COLD_START.swap(false, Ordering::SeqCst);
+        let span = invocation_span(&event, cold_start);
+        let mut metrics = (self.emf.is_some() || self.access_log.is_some())
+            .then(|| metrics::Invocation::new(&event, cold_start, self.emf.clone(), self.access_log));
+        let invocation = async move {
+            let Some(in_flight) = adapter.in_flight.enter() else {
+                return Err(Error::from("adapter is shutting down, not accepting new invocations"));
+            };
+            let span = tracing::Span::current();
+            match adapter.fetch_response(event, &mut metrics).await {
+                Ok(response) => {
+                    span.record("http.response.status_code", response.status().as_u16());
+                    if let Some(metrics) = &mut metrics {
+                        metrics.status = Some(response.status().as_u16());
+                    }
+                    Ok(response.map(|body| body.with_in_flight(in_flight).with_span(span).with_metrics(metrics)))
+                }
+                Err(e) => {
+                    span.record("otel.status_code", "ERROR");
+                    span.record("error.message", tracing::field::display(&e));
+                    Err(e)
+                }
+            }
+        };
+        Box::pin(async move {
+            let result = invocation.instrument(span).await;
+            // the span has ended; without a response body to export it, export it now
+            #[cfg(feature = "otel")]
+            if result.is_err() {
+                otel::flush_invocation().await;
+            }
+            result
+        })
+    }
+}
+
+/// Returns the route template of an API Gateway event, e.g. `/pets/{id}`.
+fn route(event: &Request) -> Option<&str> {
+    match event.request_context_ref() {
+        Some(RequestContext::ApiGatewayV1(context)) => context.resource_path.as_deref(),
+        // HTTP API route keys include the method, e.g. "GET /pets/{id}"
+        Some(RequestContext::ApiGatewayV2(context)) => context
+            .route_key
+            .as_deref()
+            .map(|route_key| route_key.split_once(' ').map_or(route_key, |(_, route)| route)),
+        _ => None,
+    }
+}
+
+/// Creates the span that covers an invocation, from the event until the response body is sent.
+///
+/// Attribute names follow the OpenTelemetry semantic conventions for FaaS and HTTP servers.
+fn invocation_span(event: &Request, cold_start: bool) -> tracing::Span {
+    let request_id = event.lambda_context_ref().map(|context| context.request_id.as_str());
+
+    let span = tracing::info_span!(
+        "invocation",
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        faas.invocation_id = request_id,
+        faas.coldstart = cold_start,
+        http.request.method = %event.method(),
+        http.route = route(event),
+        url.path = event.raw_http_path(),
+        http.response.status_code = tracing::field::Empty,
+        http.response.body.size = tracing::field::Empty,
+        error.message = tracing::field::Empty,
+    );
+    // join the Lambda trace
+    #[cfg(feature = "otel")]
+    if let Some(traceparent) = event
+        .lambda_context_ref()
+        .and_then(|context| context.xray_trace_id.as_deref())
+        .and_then(trace::traceparent)
+    {
+        otel::set_parent(&span, &traceparent);
+    }
+    span
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::GET, MockServer};
+
+    #[test]
+    fn test_parse_status_codes() {
+        assert_eq!(parse_status_codes("500,502-504,422"), vec![500, 502, 503, 504, 422]);
+        assert_eq!(
+            parse_status_codes("500, 502-504, 422"), // with spaces
+            vec![500, 502, 503, 504, 422]
+        );
+        assert_eq!(parse_status_codes("500"), vec![500]);
+        assert_eq!(parse_status_codes("500-502"), vec![500, 501, 502]);
+        assert_eq!(parse_status_codes("invalid"), Vec::<u16>::new());
+        assert_eq!(parse_status_codes("500-invalid"), Vec::<u16>::new());
+        assert_eq!(parse_status_codes(""), Vec::<u16>::new());
+    }
+
+    #[test]
+    fn test_parse_metric_dimensions() {
+        assert_eq!(
+            parse_metric_dimensions("function_name, route,STATUS"),
+            vec![
+                MetricDimension::FunctionName,
+                MetricDimension::Route,
+                MetricDimension::Status
+            ]
+        );
+        assert_eq!(parse_metric_dimensions("route,invalid"), vec![MetricDimension::Route]);
+        assert_eq!(parse_metric_dimensions(""), Vec::<MetricDimension>::new());
+    }
+
+    #[test]
+    fn test_parse_event_sources() {
+        assert_eq!(
+            parse_event_sources("SQS, sns, invalid", &Batch::SOURCES, "fan-out"),
+            vec![EventSource::Sqs]
+        );
+        assert_eq!(
+            parse_event_sources("sns,eventbridge,sqs", &envelope::SOURCES, "unwrap"),
+            vec![EventSource::Sns, EventSource::EventBridge]
+        );
+        assert_eq!(
+            parse_event_sources("", &Batch::SOURCES, "fan-out"),
+            Vec::<EventSource>::new()
+        );
+    }
+
+    #[test]
+    fn test_parse_context_headers() {
+        assert_eq!(
+            parse_context_headers("claims, SOURCE_IP,invalid"),
+            vec![ContextHeader::Claims, ContextHeader::SourceIp]
+        );
+        assert_eq!(parse_context_headers("all").len(), 5);
+        assert_eq!(parse_context_headers(""), Vec::<ContextHeader>::new());
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        assert_eq!(
+            parse_headers("Authorization: Bearer a:b; X-Probe:1;invalid;"),
+            vec![
+                ("Authorization".to_string(), "Bearer a:b".to_string()),
+                ("X-Probe".to_string(), "1".to_string()),
+            ]
+        );
+        assert!(parse_headers("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_readiness_checks_mode() {
+        let app_server = MockServer::start();
+        app_server.mock(|when, then| {
+            when.method(GET).path("/healthz");
+            then.status(200);
+        });
+        let sidecar = MockServer::start();
+        sidecar.mock(|when, then| {
+            when.method(GET).path("/ready");
+            then.status(503);
+        });
+
+        let readiness_checks = vec![
+            ReadinessCheck {
+                target: ReadinessTarget::Http(Url::parse(&app_server.url("/healthz")).unwrap()),
+                healthy_status: vec![200],
+            },
+            ReadinessCheck {
+                target: ReadinessTarget::Http(Url::parse(&sidecar.url("/ready")).unwrap()),
+                healthy_status: vec![200],
+            },
+        ];
+
+        let options = AdapterOptions {
+            readiness_checks: readiness_checks.clone(),
+            readiness_checks_mode: ReadinessCheckMode::Any,
+            ..Default::default()
+        };
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        assert!(adapter.is_web_ready().await);
+
+        let options = AdapterOptions {
+            readiness_checks,
+            readiness_checks_mode: ReadinessCheckMode::All,
+            ..Default::default()
+        };
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        let ready = timeout(Duration::from_millis(200), adapter.is_web_ready()).await;
+        assert!(ready.is_err(), "the sidecar check should keep the app not ready");
+    }
+
+    #[tokio::test]
+    async fn test_readiness_check_zero_intervals() {
+        let app_server = MockServer::start();
+        let healthcheck = app_server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(503);
+        });
+
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            readiness_check_interval_ms: 0,
+            readiness_check_log_interval_ms: 0,
+            ..Default::default()
+        };
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        let ready = timeout(Duration::from_millis(100), adapter.is_web_ready()).await;
+        assert!(ready.is_err());
+        // retried at most every 1ms rather than in a busy loop
+        assert!(healthcheck.calls() <= 100, "{} readiness checks", healthcheck.calls());
+    }
+
+    #[tokio::test]
+    async fn test_readiness_check_body() {
+        let app_server = MockServer::start();
+        let mut health = app_server.mock(|when, then| {
+            when.method(GET)
+                .path("/actuator/health")
+                .header("Authorization", "Bearer token");
+            then.status(200).body(r#"{"status":"DOWN"}"#);
+        });
+
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            readiness_check_path: "/actuator/health".to_string(),
+            readiness_check_headers: vec![("Authorization".to_string(), "Bearer token".to_string())],
+            readiness_check_body: Some(r#"json:/status == "UP""#.to_string()),
+            ..Default::default()
+        };
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        let check = adapter.readiness_checks[0].clone();
 
-        let mut app_response = self.client.request(request).await?;
+        // The app responds with 200 while it is still starting
+        assert!(adapter.check_web_readiness(&check).await.is_err());
+        health.assert();
 
-        // Check if status code should trigger an error
-        if let Some(error_codes) = &self.error_status_codes {
-            let status = app_response.status().as_u16();
-            if error_codes.contains(&status) {
-                let body_bytes = app_response
-                    .into_body()
-                    .collect()
-                    .await
-                    .map(|c| c.to_bytes())
-                    .unwrap_or_default();
-                let body_str = String::from_utf8_lossy(&body_bytes);
-                return Err(Error::from(format!(
-                    "{{\"statusCode\":{},\"body\":{}}}",
-                    status,
-                    serde_json::to_string(&*body_str).unwrap_or_else(|_| format!("\"{}\"", body_str))
-                )));
-            }
-        }
+        health.delete();
+        app_server.mock(|when, then| {
+            when.method(GET)
+                .path("/actuator/health")
+                .header("Authorization", "Bearer token");
+            then.status(200).body(r#"{"status":"UP"}"#);
+        });
+        assert!(adapter.check_web_readiness(&check).await.is_ok());
+    }
 
-        // remove "transfer-encoding" from the response to support "sam local start-api"
-        app_response.headers_mut().remove("transfer-encoding");
+    #[test]
+    fn test_invalid_readiness_check_body() {
+        let options = AdapterOptions {
+            readiness_check_body: Some("regex:(".to_string()),
+            ..Default::default()
+        };
+        assert!(Adapter::new(&options).is_err());
+    }
 
-        tracing::debug!(status = %app_response.status(), body_size = ?app_response.body().size_hint().lower(),
-            app_headers = ?app_response.headers().clone(), "responding to lambda event");
+    #[tokio::test]
+    async fn test_liveness_check_failure() {
+        let app_server = MockServer::start();
+        app_server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(200);
+        });
+        let liveness = app_server.mock(|when, then| {
+            when.method(GET).path("/live");
+            then.status(500);
+        });
 
-        Ok(app_response)
-    }
-}
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            readiness_check_healthy_status: vec![200],
+            liveness_check_path: Some("/live".to_string()),
+            liveness_check_interval_ms: 20,
+            liveness_check_failure_threshold: 2,
+            ..Default::default()
+        };
+        let mut adapter = Adapter::new(&options).expect("Failed to create adapter");
+        adapter.check_init_health().await.unwrap();
+        assert!(adapter.ready_at_init.load(Ordering::SeqCst));
 
-/// Implementation of [`tower::Service`] for the adapter.
-///
-/// This allows the adapter to be used directly with the Lambda runtime,
-/// which expects a `Service` that can handle Lambda events.
-impl Service<Request> for Adapter<HttpConnector, Body> {
-    type Response = Response<Incoming>;
-    type Error = Error;
-    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+        tokio::time::sleep(Duration::from_millis(200)).await;
 
-    fn poll_ready(&mut self, _cx: &mut core::task::Context<'_>) -> core::task::Poll<Result<(), Self::Error>> {
-        core::task::Poll::Ready(Ok(()))
+        // the next request waits for the readiness check again
+        assert!(liveness.calls() >= 2);
+        assert!(!adapter.ready_at_init.load(Ordering::SeqCst));
     }
 
-    fn call(&mut self, event: Request) -> Self::Future {
-        let adapter = self.clone();
-        Box::pin(async move { adapter.fetch_response(event).await })
-    }
-}
+    #[tokio::test]
+    async fn test_liveness_check_interval_zero() {
+        let app_server = MockServer::start();
+        app_server.mock(|when, then| {
+            when.method(GET).path("/");
+            then.status(200);
+        });
+        let liveness = app_server.mock(|when, then| {
+            when.method(GET).path("/live");
+            then.status(200);
+        });
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use httpmock::{Method::GET, MockServer};
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            liveness_check_path: Some("/live".to_string()),
+            liveness_check_interval_ms: 0,
+            ..Default::default()
+        };
+        // tokio::time::interval panics on a zero period
+        let mut adapter = Adapter::new(&options).expect("Failed to create adapter");
+        assert_eq!(adapter.liveness_interval, Duration::from_millis(1));
+        adapter.check_init_health().await.unwrap();
 
-    #[test]
-    fn test_parse_status_codes() {
-        assert_eq!(parse_status_codes("500,502-504,422"), vec![500, 502, 503, 504, 422]);
-        assert_eq!(
-            parse_status_codes("500, 502-504, 422"), // with spaces
-            vec![500, 502, 503, 504, 422]
-        );
-        assert_eq!(parse_status_codes("500"), vec![500]);
-        assert_eq!(parse_status_codes("500-502"), vec![500, 501, 502]);
-        assert_eq!(parse_status_codes("invalid"), Vec::<u16>::new());
-        assert_eq!(parse_status_codes("500-invalid"), Vec::<u16>::new());
-        assert_eq!(parse_status_codes(""), Vec::<u16>::new());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(liveness.calls() > 0);
     }
 
     #[tokio::test]
@@ -1051,12 +3152,11 @@ mod tests {
         // Initialize adapter and do readiness check
         let adapter = Adapter::new(&options).expect("Failed to create adapter");
 
-        let url = adapter.healthcheck_url.clone();
-        let protocol = adapter.healthcheck_protocol;
+        let check = adapter.readiness_checks[0].clone();
 
         //adapter.check_init_health().await;
 
-        assert!(adapter.check_web_readiness(&url, &protocol).await.is_ok());
+        assert!(adapter.check_web_readiness(&check).await.is_ok());
 
         // Assert app server's healthcheck endpoint got called
         healthcheck.assert();
@@ -1083,12 +3183,11 @@ mod tests {
         // Initialize adapter and do readiness check
         let adapter = Adapter::new(&options).expect("Failed to create adapter");
 
-        let url = adapter.healthcheck_url.clone();
-        let protocol = adapter.healthcheck_protocol;
+        let check = adapter.readiness_checks[0].clone();
 
         //adapter.check_init_health().await;
 
-        assert!(adapter.check_web_readiness(&url, &protocol).await.is_err());
+        assert!(adapter.check_web_readiness(&check).await.is_err());
 
         // Assert app server's healthcheck endpoint got called
         healthcheck.assert();
@@ -1116,12 +3215,11 @@ mod tests {
         // Initialize adapter and do readiness check
         let adapter = Adapter::new(&options).expect("Failed to create adapter");
 
-        let url = adapter.healthcheck_url.clone();
-        let protocol = adapter.healthcheck_protocol;
+        let check = adapter.readiness_checks[0].clone();
 
         //adapter.check_init_health().await;
 
-        assert!(adapter.check_web_readiness(&url, &protocol).await.is_err());
+        assert!(adapter.check_web_readiness(&check).await.is_err());
 
         // Assert app server's healthcheck endpoint got called
         healthcheck.assert();
@@ -1143,11 +3241,10 @@ mod tests {
         };
 
         let adapter = Adapter::new(&options).expect("Failed to create adapter");
-        let url = adapter.healthcheck_url.clone();
-        let protocol = adapter.healthcheck_protocol;
+        let check = adapter.readiness_checks[0].clone();
 
-        assert_eq!(protocol, Protocol::Tcp);
-        assert!(adapter.check_web_readiness(&url, &protocol).await.is_ok());
+        assert_eq!(check.protocol(), Protocol::Tcp);
+        assert!(adapter.check_web_readiness(&check).await.is_ok());
     }
 
     #[tokio::test]
@@ -1163,10 +3260,90 @@ mod tests {
         };
 
         let adapter = Adapter::new(&options).expect("Failed to create adapter");
-        let url = adapter.healthcheck_url.clone();
-        let protocol = adapter.healthcheck_protocol;
+        let check = adapter.readiness_checks[0].clone();
+
+        assert!(adapter.check_web_readiness(&check).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_readiness_check_unix_socket() {
+        let path = env::temp_dir().join(format!("lwa-readiness-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let options = AdapterOptions {
+            unix_socket: Some(path.to_string_lossy().to_string()),
+            readiness_check_protocol: Protocol::Tcp,
+            ..Default::default()
+        };
+
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        let check = adapter.readiness_checks[0].clone();
+
+        // Nothing is listening on the socket yet
+        assert!(adapter.check_web_readiness(&check).await.is_err());
+
+        let _listener = tokio::net::UnixListener::bind(&path).unwrap();
+        assert!(adapter.check_web_readiness(&check).await.is_ok());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_file_readiness_check() {
+        let path = env::temp_dir().join(format!("lwa-ready-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let options = AdapterOptions {
+            readiness_check_protocol: Protocol::File,
+            readiness_check_file: Some(path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        let check = adapter.readiness_checks[0].clone();
+
+        assert!(adapter.check_web_readiness(&check).await.is_err());
+
+        std::fs::write(&path, "").unwrap();
+        assert!(adapter.check_web_readiness(&check).await.is_ok());
+
+        let _ = std::fs::remove_file(&path);
+
+        // the file is required, the HTTP path `/` always exists
+        let options = AdapterOptions {
+            readiness_check_protocol: Protocol::File,
+            ..Default::default()
+        };
+        assert!(Adapter::new(&options).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exec_readiness_check() {
+        let options = AdapterOptions {
+            readiness_check_protocol: Protocol::Exec,
+            readiness_check_command: Some("test -n \"$AWS_LWA_EXEC_READY\" || exit 1".to_string()),
+            ..Default::default()
+        };
+
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        let check = adapter.readiness_checks[0].clone();
+        assert_eq!(check.to_string(), "exec:test -n \"$AWS_LWA_EXEC_READY\" || exit 1");
+        assert!(adapter.check_web_readiness(&check).await.is_err());
 
-        assert!(adapter.check_web_readiness(&url, &protocol).await.is_err());
+        let options = AdapterOptions {
+            readiness_check_protocol: Protocol::Exec,
+            readiness_check_command: Some("exit 0".to_string()),
+            ..Default::default()
+        };
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+        assert!(adapter.check_web_readiness(&adapter.readiness_checks[0]).await.is_ok());
+
+        // the command is required
+        let options = AdapterOptions {
+            readiness_check_protocol: Protocol::Exec,
+            ..Default::default()
+        };
+        assert!(Adapter::new(&options).is_err());
     }
 
     #[test]
@@ -1175,10 +3352,49 @@ mod tests {
         assert_eq!(Protocol::from("HTTP"), Protocol::Http);
         assert_eq!(Protocol::from("tcp"), Protocol::Tcp);
         assert_eq!(Protocol::from("TCP"), Protocol::Tcp);
+        assert_eq!(Protocol::from("exec"), Protocol::Exec);
+        assert_eq!(Protocol::from("File"), Protocol::File);
         assert_eq!(Protocol::from("unknown"), Protocol::Http); // defaults to Http
         assert_eq!(Protocol::from(""), Protocol::Http);
     }
 
+    #[test]
+    fn test_upstream_protocol_from_str() {
+        assert_eq!(UpstreamProtocol::from("http1"), UpstreamProtocol::Http1);
+        assert_eq!(UpstreamProtocol::from("h2c"), UpstreamProtocol::H2c);
+        assert_eq!(UpstreamProtocol::from("H2C"), UpstreamProtocol::H2c);
+        assert_eq!(UpstreamProtocol::from("auto"), UpstreamProtocol::Auto);
+        assert_eq!(UpstreamProtocol::from("unknown"), UpstreamProtocol::Http1); // defaults to Http1
+        assert_eq!(UpstreamProtocol::from(""), UpstreamProtocol::Http1);
+    }
+
+    #[test]
+    fn test_request_timeout_action_from_str() {
+        assert_eq!(RequestTimeoutAction::from("response"), RequestTimeoutAction::Response);
+        assert_eq!(RequestTimeoutAction::from("ERROR"), RequestTimeoutAction::Error);
+        assert_eq!(RequestTimeoutAction::from("unknown"), RequestTimeoutAction::Response);
+        // defaults to Response
+    }
+
+    #[test]
+    fn test_forwarded_headers_from_str() {
+        assert_eq!(ForwardedHeaders::from("append"), ForwardedHeaders::Append);
+        assert_eq!(ForwardedHeaders::from("Overwrite"), ForwardedHeaders::Overwrite);
+        assert_eq!(ForwardedHeaders::from("unknown"), ForwardedHeaders::Off); // defaults to Off
+        assert_eq!(ForwardedStyle::from("x-forwarded"), ForwardedStyle::XForwarded);
+        assert_eq!(ForwardedStyle::from("RFC7239"), ForwardedStyle::Rfc7239);
+        assert_eq!(ForwardedStyle::from("both"), ForwardedStyle::Both);
+        assert_eq!(ForwardedStyle::from("unknown"), ForwardedStyle::XForwarded);
+        // defaults to XForwarded
+    }
+
+    #[test]
+    fn test_liveness_action_from_str() {
+        assert_eq!(LivenessAction::from("wait"), LivenessAction::Wait);
+        assert_eq!(LivenessAction::from("EXIT"), LivenessAction::Exit);
+        assert_eq!(LivenessAction::from("unknown"), LivenessAction::Wait); // defaults to Wait
+    }
+
     #[test]
     fn test_invoke_mode_from_str() {
         assert_eq!(LambdaInvokeMode::from("buffered"), LambdaInvokeMode::Buffered);
@@ -1341,10 +3557,293 @@ mod tests {
         let mut request = Request::from(alb_req);
         request.extensions_mut().insert(make_lambda_context(Some("tenant-abc")));
 
-        let response = adapter.fetch_response(request).await.expect("Request failed");
+        let response = adapter
+            .fetch_response(request, &mut None)
+            .await
+            .expect("Request failed");
         assert_eq!(200, response.status().as_u16());
     }
 
+    #[tokio::test]
+    async fn test_extension_shutdown_event() {
+        use httpmock::Method::POST;
+
+        let runtime_api = MockServer::start();
+        let register = runtime_api.mock(|when, then| {
+            when.method(POST)
+                .path("/2020-01-01/extension/register")
+                .header("Lambda-Extension-Name", "lambda-adapter")
+                .body_includes("SHUTDOWN");
+            then.status(200).header("Lambda-Extension-Identifier", "ext-id");
+        });
+        let deadline_ms = (SystemTime::now() + Duration::from_secs(2))
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let next = runtime_api.mock(|when, then| {
+            when.method(GET)
+                .path("/2020-01-01/extension/event/next")
+                .header("Lambda-Extension-Identifier", "ext-id");
+            then.status(200).json_body(serde_json::json!({
+                "eventType": "SHUTDOWN",
+                "shutdownReason": "spindown",
+                "deadlineMs": deadline_ms,
+            }));
+        });
+
+        let app_server = MockServer::start();
+        let shutdown_hook = app_server.mock(|when, then| {
+            when.method(POST).path("/shutdown");
+            then.status(200);
+        });
+
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            shutdown_path: Some("/shutdown".to_string()),
+            ..Default::default()
+        };
+        let mut adapter = Adapter::new(&options).expect("Failed to create adapter");
+
+        adapter
+            .register_extension_internal(&runtime_api.address().to_string())
+            .await
+            .expect("Extension should shut down cleanly");
+
+        register.assert();
+        next.assert();
+        shutdown_hook.assert();
+
+        // new invocations are rejected after shutdown
+        let alb_req = lambda_http::request::LambdaRequest::Alb({
+            let mut req = lambda_http::aws_lambda_events::alb::AlbTargetGroupRequest::default();
+            req.http_method = Method::GET;
+            req.path = Some("/hello".into());
+            req
+        });
+        let mut request = Request::from(alb_req);
+        request.extensions_mut().insert(make_lambda_context(None));
+
+        let result = adapter.call(request).await;
+        assert!(result.unwrap_err().to_string().contains("shutting down"));
+    }
+
+    /// Name and fields of a closed span.
+    type RecordedSpan = (String, Vec<(String, String)>);
+
+    /// Records each closed span.
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<std::sync::Mutex<Vec<RecordedSpan>>>);
+
+    #[derive(Default)]
+    struct SpanFields(Vec<(String, String)>);
+
+    impl tracing::field::Visit for SpanFields {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanRecorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = SpanFields::default();
+            attrs.record(&mut fields);
+            ctx.span(id).unwrap().extensions_mut().insert(fields);
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            values.record(ctx.span(id).unwrap().extensions_mut().get_mut::<SpanFields>().unwrap());
+        }
+
+        fn on_close(&self, id: tracing::span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let fields = span.extensions_mut().remove::<SpanFields>().unwrap_or_default();
+            self.0.lock().unwrap().push((span.name().to_string(), fields.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invocation_spans() {
+        use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+        let recorder = SpanRecorder::default();
+        let _subscriber = tracing_subscriber::registry().with(recorder.clone()).set_default();
+
+        let app_server = MockServer::start();
+        app_server.mock(|when, then| {
+            when.method(GET).path("/hello");
+            then.status(201).body("hello");
+        });
+
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            ..Default::default()
+        };
+        let mut adapter = Adapter::new(&options).expect("Failed to create adapter");
+
+        let alb_req = lambda_http::request::LambdaRequest::Alb({
+            let mut req = lambda_http::aws_lambda_events::alb::AlbTargetGroupRequest::default();
+            req.http_method = Method::GET;
+            req.path = Some("/hello".into());
+            req
+        });
+        let mut request = Request::from(alb_req);
+        request.extensions_mut().insert(make_lambda_context(None));
+
+        let response = adapter.call(request).await.expect("Request failed");
+        assert!(
+            !recorder.0.lock().unwrap().iter().any(|(name, _)| name == "invocation"),
+            "invocation span should stay open until the body is sent"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello");
+
+        let spans = recorder.0.lock().unwrap();
+        let names: Vec<&str> = spans.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["decode_event", "upstream_request", "response_stream", "invocation"]
+        );
+
+        let (_, invocation) = spans.last().unwrap();
+        let field = |name: &str| {
+            invocation
+                .iter()
+                .rev()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(field("faas.invocation_id"), Some("\"test-id\""));
+        assert_eq!(field("url.path"), Some("\"/hello\""));
+        assert_eq!(field("http.response.status_code"), Some("201"));
+        assert_eq!(field("http.response.body.size"), Some("5"));
+    }
+
+    #[cfg(feature = "otel")]
+    #[tokio::test]
+    async fn test_invocation_spans_join_lambda_trace() {
+        use opentelemetry::trace::{SpanId, TraceId, TracerProvider as _};
+        use opentelemetry_sdk::error::OTelSdkResult;
+        use opentelemetry_sdk::trace::{SdkTracerProvider, SpanData, SpanExporter};
+        use std::sync::Mutex;
+        use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+        #[derive(Clone, Debug, Default)]
+        struct Exporter(Arc<Mutex<Vec<SpanData>>>);
+
+        impl SpanExporter for Exporter {
+            async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+                self.0.lock().unwrap().extend(batch);
+                Ok(())
+            }
+        }
+
+        let exporter = Exporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let _subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")))
+            .set_default();
+
+        let traceparent = Arc::new(Mutex::new(None));
+        let app_server = MockServer::start();
+        app_server.mock(|when, then| {
+            let traceparent = traceparent.clone();
+            when.method(GET).path("/hello").is_true(move |req| {
+                *traceparent.lock().unwrap() = req
+                    .headers()
+                    .get("traceparent")
+                    .map(|value| value.to_str().unwrap().to_string());
+                true
+            });
+            then.status(200).body("hello");
+        });
+
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            ..Default::default()
+        };
+        let mut adapter = Adapter::new(&options).expect("Failed to create adapter");
+
+        let alb_req = lambda_http::request::LambdaRequest::Alb({
+            let mut req = lambda_http::aws_lambda_events::alb::AlbTargetGroupRequest::default();
+            req.http_method = Method::GET;
+            req.path = Some("/hello".into());
+            req
+        });
+        let mut request = Request::from(alb_req);
+        let mut context = make_lambda_context(None);
+        context.xray_trace_id =
+            Some("Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1".to_string());
+        request.extensions_mut().insert(context);
+
+        let response = adapter.call(request).await.expect("Request failed");
+        response.into_body().collect().await.unwrap();
+
+        let spans = exporter.0.lock().unwrap();
+        let span = |name: &str| spans.iter().find(|span| span.name == name).unwrap();
+        let invocation = span("invocation");
+        let upstream = span("upstream_request");
+
+        let trace_id = TraceId::from_hex("5759e988bd862e3fe1be46a994272793").unwrap();
+        assert_eq!(invocation.span_context.trace_id(), trace_id);
+        assert_eq!(invocation.parent_span_id, SpanId::from_hex("53995c3f42cd8ad8").unwrap());
+        assert_eq!(upstream.span_context.trace_id(), trace_id);
+        assert_eq!(upstream.parent_span_id, invocation.span_context.span_id());
+        assert_eq!(
+            traceparent.lock().unwrap().as_deref(),
+            Some(format!("00-{}-{}-01", trace_id, upstream.span_context.span_id()).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forward_telemetry_to_app() {
+        use httpmock::Method::POST;
+
+        let batch = r#"[{"time":"2024-01-01T00:00:00.000Z","type":"platform.report","record":{"requestId":"abc"}}]"#;
+        let app_server = MockServer::start();
+        let telemetry = app_server.mock(|when, then| {
+            when.method(POST)
+                .path("/_telemetry")
+                .header("content-type", "application/json")
+                .body(batch);
+            then.status(202);
+        });
+
+        let options = AdapterOptions {
+            host: app_server.host(),
+            port: app_server.port().to_string(),
+            readiness_check_port: app_server.port().to_string(),
+            telemetry: TelemetryMode::App,
+            telemetry_path: "/_telemetry".to_string(),
+            ..Default::default()
+        };
+        let adapter = Adapter::new(&options).expect("Failed to create adapter");
+
+        adapter.forward_telemetry(Bytes::from(batch)).await;
+
+        telemetry.assert();
+    }
+
     #[tokio::test]
     async fn test_tenant_id_header_absent_when_no_tenant() {
         let app_server = MockServer::start();
@@ -1374,7 +3873,10 @@ mod tests {
         let mut request = Request::from(alb_req);
         request.extensions_mut().insert(make_lambda_context(None));
 
-        let response = adapter.fetch_response(request).await.expect("Request failed");
+        let response = adapter
+            .fetch_response(request, &mut None)
+            .await
+            .expect("Request failed");
         assert_eq!(200, response.status().as_u16());
     }
 }
@@ -1,7 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use lambda_web_adapter::{tracing, Adapter, AdapterOptions, Error};
+use lambda_web_adapter::{Adapter, AdapterOptions, Error};
 
 fn main() -> Result<(), Error> {
     // Apply runtime proxy configuration BEFORE starting tokio runtime
@@ -20,7 +20,11 @@ async fn async_main() -> Result<(), Error> {
     // Initialize tracing with Lambda's advanced logging controls support.
     // This respects AWS_LAMBDA_LOG_LEVEL and AWS_LAMBDA_LOG_FORMAT environment variables
     // set by Lambda's advanced logging configuration.
-    tracing::init_default_subscriber();
+    #[cfg(not(feature = "otel"))]
+    lambda_web_adapter::tracing::init_default_subscriber();
+    // With the otel feature, invocation spans are also exported over OTLP
+    #[cfg(feature = "otel")]
+    lambda_web_adapter::otel::init_subscriber()?;
 
     // get configuration options from environment variables
     let options = AdapterOptions::default();
@@ -29,8 +33,10 @@ async fn async_main() -> Result<(), Error> {
     let mut adapter = Adapter::new(&options)?;
     // register the adapter as an extension
     adapter.register_default_extension();
+    // start the web application if the adapter is configured to supervise it
+    adapter.start_app_process();
     // check if the web application is ready
-    adapter.check_init_health().await;
+    adapter.check_init_health().await?;
     // start lambda runtime after the web application is ready
     adapter.run().await?;
 
@@ -0,0 +1,232 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::access_log::{self, Format};
+use crate::MetricDimension;
+use lambda_http::{Request, RequestExt};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Writes per-invocation metrics to stdout in CloudWatch Embedded Metric Format (EMF).
+#[derive(Debug)]
+pub(crate) struct Emf {
+    namespace: String,
+    dimensions: Vec<MetricDimension>,
+    function_name: String,
+}
+
+impl Emf {
+    pub(crate) fn new(namespace: &str, dimensions: &[MetricDimension]) -> Self {
+        Emf {
+            namespace: namespace.to_string(),
+            dimensions: dimensions.to_vec(),
+            function_name: std::env::var("AWS_LAMBDA_FUNCTION_NAME").unwrap_or_default(),
+        }
+    }
+
+    /// Builds the EMF record of an invocation.
+    fn record(&self, invocation: &Invocation, timestamp: SystemTime) -> Value {
+        let status_class = match invocation.status {
+            Some(status) => format!("{}xx", status / 100),
+            None => "error".to_string(),
+        };
+
+        let mut record = Map::new();
+        let mut dimension_names = Vec::new();
+        for dimension in &self.dimensions {
+            let (name, value) = match dimension {
+                MetricDimension::FunctionName => ("FunctionName", self.function_name.as_str()),
+                MetricDimension::Route => ("Route", invocation.route.as_deref().unwrap_or("unknown")),
+                MetricDimension::Status => ("StatusClass", status_class.as_str()),
+            };
+            dimension_names.push(name);
+            record.insert(name.to_string(), json!(value));
+        }
+
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let class = |class: u16| invocation.status.is_some_and(|status| status / 100 == class) as u8;
+        let metrics = [
+            (
+                "UpstreamLatency",
+                "Milliseconds",
+                json!(millis(invocation.upstream_latency)),
+            ),
+            (
+                "ReadinessWait",
+                "Milliseconds",
+                json!(millis(invocation.readiness_wait)),
+            ),
+            ("ResponseBytes", "Bytes", json!(invocation.response_bytes)),
+            ("ColdStart", "Count", json!(invocation.cold_start as u8)),
+            ("Status2xx", "Count", json!(class(2))),
+            ("Status3xx", "Count", json!(class(3))),
+            ("Status4xx", "Count", json!(class(4))),
+            ("Status5xx", "Count", json!(class(5))),
+            ("Errors", "Count", json!(invocation.status.is_none() as u8)),
+        ];
+        for (name, _, value) in &metrics {
+            record.insert(name.to_string(), value.clone());
+        }
+
+        record.insert("RequestId".to_string(), json!(invocation.request_id));
+        if let Some(status) = invocation.status {
+            record.insert("StatusCode".to_string(), json!(status));
+        }
+        record.insert(
+            "_aws".to_string(),
+            json!({
+                "Timestamp": timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+                "CloudWatchMetrics": [{
+                    "Namespace": self.namespace,
+                    "Dimensions": [dimension_names],
+                    "Metrics": metrics
+                        .iter()
+                        .map(|(name, unit, _)| json!({ "Name": name, "Unit": unit }))
+                        .collect::<Vec<_>>(),
+                }],
+            }),
+        );
+
+        Value::Object(record)
+    }
+}
+
+/// Measurements of a single invocation.
+///
+/// The EMF record and the access log line are written when this is dropped, which
+/// happens once the response body has been sent, so `response_bytes` covers
+/// streamed responses.
+#[derive(Debug)]
+pub(crate) struct Invocation {
+    emf: Option<Arc<Emf>>,
+    access_log: Option<Format>,
+    pub(crate) request_id: String,
+    route: Option<String>,
+    pub(crate) method: String,
+    /// Path forwarded to the app, after the base path is removed.
+    pub(crate) path: String,
+    /// HTTP version of the client's request, `None` if the event doesn't have it.
+    pub(crate) protocol: Option<String>,
+    pub(crate) source: &'static str,
+    pub(crate) cold_start: bool,
+    pub(crate) readiness_wait: Duration,
+    pub(crate) upstream_latency: Duration,
+    /// Status code returned by the app, `None` if the invocation failed.
+    pub(crate) status: Option<u16>,
+    pub(crate) response_bytes: u64,
+}
+
+impl Invocation {
+    pub(crate) fn new(event: &Request, cold_start: bool, emf: Option<Arc<Emf>>, access_log: Option<Format>) -> Self {
+        Invocation {
+            emf,
+            access_log,
+            request_id: event
+                .lambda_context_ref()
+                .map_or_else(String::new, |context| context.request_id.clone()),
+            route: crate::route(event).map(str::to_string),
+            method: event.method().to_string(),
+            path: event.raw_http_path().to_string(),
+            protocol: access_log::protocol(event),
+            source: access_log::event_source(event),
+            cold_start,
+            readiness_wait: Duration::ZERO,
+            upstream_latency: Duration::ZERO,
+            status: None,
+            response_bytes: 0,
+        }
+    }
+}
+
+impl Drop for Invocation {
+    fn drop(&mut self) {
+        let now = SystemTime::now();
+        if let Some(emf) = &self.emf {
+            // EMF records must be written as a single line
+            println!("{}", emf.record(self, now));
+        }
+        if let Some(format) = self.access_log {
+            println!("{}", access_log::line(self, format, now));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invocation() -> Invocation {
+        let mut invocation = Invocation::new(&Request::default(), true, None, None);
+        invocation.request_id = "req-1".to_string();
+        invocation.route = Some("/pets/{id}".to_string());
+        invocation.readiness_wait = Duration::from_millis(250);
+        invocation.upstream_latency = Duration::from_micros(12_500);
+        invocation.status = Some(404);
+        invocation.response_bytes = 42;
+        invocation
+    }
+
+    #[test]
+    fn test_emf_record() {
+        let emf = Emf {
+            namespace: "MyApp".to_string(),
+            dimensions: vec![
+                MetricDimension::FunctionName,
+                MetricDimension::Route,
+                MetricDimension::Status,
+            ],
+            function_name: "my-function".to_string(),
+        };
+        let record = emf.record(&invocation(), UNIX_EPOCH + Duration::from_millis(1_700_000_000_000));
+
+        assert_eq!(record["_aws"]["Timestamp"], 1_700_000_000_000u64);
+        let directive = &record["_aws"]["CloudWatchMetrics"][0];
+        assert_eq!(directive["Namespace"], "MyApp");
+        assert_eq!(
+            directive["Dimensions"],
+            json!([["FunctionName", "Route", "StatusClass"]])
+        );
+        assert_eq!(
+            directive["Metrics"][0],
+            json!({ "Name": "UpstreamLatency", "Unit": "Milliseconds" })
+        );
+
+        assert_eq!(record["FunctionName"], "my-function");
+        assert_eq!(record["Route"], "/pets/{id}");
+        assert_eq!(record["StatusClass"], "4xx");
+        assert_eq!(record["UpstreamLatency"], 12.5);
+        assert_eq!(record["ReadinessWait"], 250.0);
+        assert_eq!(record["ResponseBytes"], 42);
+        assert_eq!(record["ColdStart"], 1);
+        assert_eq!(record["Status2xx"], 0);
+        assert_eq!(record["Status4xx"], 1);
+        assert_eq!(record["Errors"], 0);
+        assert_eq!(record["StatusCode"], 404);
+        assert_eq!(record["RequestId"], "req-1");
+    }
+
+    #[test]
+    fn test_emf_record_failed_invocation() {
+        let emf = Emf {
+            namespace: "MyApp".to_string(),
+            dimensions: vec![MetricDimension::Route, MetricDimension::Status],
+            function_name: "my-function".to_string(),
+        };
+        let mut invocation = invocation();
+        invocation.route = None;
+        invocation.status = None;
+        let record = emf.record(&invocation, SystemTime::now());
+
+        assert_eq!(
+            record["_aws"]["CloudWatchMetrics"][0]["Dimensions"],
+            json!([["Route", "StatusClass"]])
+        );
+        assert!(record.get("FunctionName").is_none());
+        assert_eq!(record["Route"], "unknown");
+        assert_eq!(record["StatusClass"], "error");
+        assert_eq!(record["Status4xx"], 0);
+        assert_eq!(record["Errors"], 1);
+        assert!(record.get("StatusCode").is_none());
+    }
+}
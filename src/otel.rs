@@ -0,0 +1,144 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! OpenTelemetry export of the adapter's invocation spans.
+//!
+//! Enabled with the `otel` cargo feature. Each invocation produces an `invocation`
+//! span with `readiness_wait`, `decode_event`, `upstream_request` and
+//! `response_stream` children, exported over OTLP/HTTP, e.g. to the ADOT collector
+//! extension on `localhost`.
+
+use crate::Error;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use std::{collections::HashMap, env, str::FromStr, sync::OnceLock, time::Duration};
+use tracing::Level;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{
+    filter::{LevelFilter, Targets},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+const ENV_OTEL_ENDPOINT: &str = "AWS_LWA_OTEL_ENDPOINT";
+
+/// How long an invocation waits for its spans to be exported.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+static TRACER_PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Initializes `tracing` to write logs like [`lambda_http::tracing::init_default_subscriber()`]
+/// and to export spans over OTLP/HTTP.
+///
+/// The collector endpoint is read from `AWS_LWA_OTEL_ENDPOINT` (e.g. `http://localhost:4318`),
+/// then from the standard `OTEL_EXPORTER_OTLP_ENDPOINT` variables, and defaults to
+/// `http://localhost:4318`. The service name is `OTEL_SERVICE_NAME`, or the function name.
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be created or a global subscriber is already set.
+pub fn init_subscriber() -> Result<(), Error> {
+    let mut exporter = SpanExporter::builder().with_http();
+    if let Ok(endpoint) = env::var(ENV_OTEL_ENDPOINT) {
+        exporter = exporter.with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')));
+    }
+
+    let mut resource = Resource::builder();
+    if env::var("OTEL_SERVICE_NAME").is_err() {
+        if let Ok(function_name) = env::var("AWS_LAMBDA_FUNCTION_NAME") {
+            resource = resource.with_service_name(function_name);
+        }
+    }
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter.build()?)
+        .with_resource(resource.build())
+        .build();
+    let tracer = provider.tracer("lambda_web_adapter");
+    let _ = TRACER_PROVIDER.set(provider);
+
+    // same log level and format handling as lambda_http's default subscriber
+    let log_format = env::var("AWS_LAMBDA_LOG_FORMAT").unwrap_or_default();
+    let log_level_str = env::var("AWS_LAMBDA_LOG_LEVEL").or_else(|_| env::var("RUST_LOG"));
+    let log_level = LevelFilter::from_str(log_level_str.as_deref().unwrap_or("INFO")).unwrap_or(LevelFilter::INFO);
+
+    let log_filter = EnvFilter::builder()
+        .with_default_directive(log_level.into())
+        .from_env_lossy();
+    // the log level doesn't apply to the exported spans, which are all at INFO
+    let registry = tracing_subscriber::registry().with(
+        tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .with_filter(Targets::new().with_target("lambda_web_adapter", Level::INFO)),
+    );
+    let fmt = tracing_subscriber::fmt::layer().with_target(false).without_time();
+
+    if log_format.eq_ignore_ascii_case("json") {
+        registry.with(fmt.json().with_filter(log_filter)).try_init()?;
+    } else {
+        registry.with(fmt.with_filter(log_filter)).try_init()?;
+    }
+
+    Ok(())
+}
+
+/// Makes `span` a child of the span in a W3C `traceparent` header value, e.g. the
+/// Lambda trace. Must be called before `span` has children.
+pub(crate) fn set_parent(span: &Span, traceparent: &str) {
+    let carrier = HashMap::from([("traceparent".to_string(), traceparent.to_string())]);
+    if let Err(e) = span.set_parent(TraceContextPropagator::new().extract(&carrier)) {
+        tracing::debug!(error = %e, "failed to set the parent of the span");
+    }
+}
+
+/// Sets `traceparent` to the context of `span`, so the app's spans are its children.
+///
+/// Leaves `headers` unchanged if `span` is not exported.
+pub(crate) fn inject_traceparent(span: &Span, headers: &mut HeaderMap) {
+    let mut carrier = HashMap::new();
+    TraceContextPropagator::new().inject_context(&span.context(), &mut carrier);
+    if let Some(value) = carrier
+        .get("traceparent")
+        .and_then(|value| HeaderValue::from_str(value).ok())
+    {
+        headers.insert(HeaderName::from_static("traceparent"), value);
+    }
+}
+
+/// Exports the spans that are still buffered, on a blocking thread, waiting at most
+/// [`FLUSH_TIMEOUT`].
+pub(crate) async fn flush_invocation() {
+    if TRACER_PROVIDER.get().is_none() {
+        return;
+    }
+    if tokio::time::timeout(FLUSH_TIMEOUT, tokio::task::spawn_blocking(flush))
+        .await
+        .is_err()
+    {
+        tracing::warn!("spans were not exported within {}ms", FLUSH_TIMEOUT.as_millis());
+    }
+}
+
+/// Starts [`flush_invocation`] without waiting for it, e.g. when a response body is dropped.
+pub(crate) fn spawn_flush_invocation() {
+    if TRACER_PROVIDER.get().is_none() {
+        return;
+    }
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        runtime.spawn(flush_invocation());
+    }
+}
+
+/// Exports the spans that are still buffered. Blocks the current thread.
+pub(crate) fn flush() {
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        if let Err(e) = provider.force_flush() {
+            tracing::warn!(error = %e, "failed to flush spans");
+        }
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::process::Stdio;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{watch, Notify};
+
+/// Delay before the first restart of a crashed application.
+const RESTART_BACKOFF_INITIAL: Duration = Duration::from_millis(100);
+/// Upper bound for the delay between restarts.
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+/// The web application process spawned and supervised by the adapter.
+///
+/// The command runs through `/bin/sh -c`. Its stdout and stderr are forwarded
+/// line by line to `tracing`. When the process exits, the adapter is marked not
+/// ready and the command is restarted with exponential backoff, unless it was
+/// stopped with [`AppProcess::terminate`].
+pub(crate) struct AppProcess {
+    command: String,
+    /// Process ID of the running application, `None` while it is not running.
+    pid: watch::Sender<Option<u32>>,
+    stopping: AtomicBool,
+    /// Wakes the supervisor when the application is stopped while it waits to restart it.
+    stopped: Notify,
+}
+
+impl AppProcess {
+    pub(crate) fn new(command: &str) -> Self {
+        AppProcess {
+            command: command.to_string(),
+            pid: watch::Sender::new(None),
+            stopping: AtomicBool::new(false),
+            stopped: Notify::new(),
+        }
+    }
+
+    /// Sends `SIGTERM` to the application's process group and stops restarting it.
+    pub(crate) fn terminate(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.stopped.notify_one();
+        if let Some(pid) = *self.pid.borrow() {
+            tracing::info!(pid, "sending SIGTERM to app process");
+            // SAFETY: kill(2) has no memory safety requirements. The application runs in its
+            // own process group (see `spawn`), so a negative pid signals the shell and its children.
+            if unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGTERM) } != 0 {
+                tracing::warn!(pid, error = %std::io::Error::last_os_error(), "failed to signal app process");
+            }
+        }
+    }
+
+    /// Waits until the application process is no longer running.
+    pub(crate) async fn exited(&self) {
+        let mut pid = self.pid.subscribe();
+        // the sender lives as long as `self`, so this cannot fail
+        let _ = pid.wait_for(Option::is_none).await;
+    }
+
+    /// Spawns a background task that starts the application and restarts it whenever it exits.
+    ///
+    /// `ready` is set to `false` each time the process exits, so the next
+    /// invocation waits for the restarted application to pass the readiness check.
+    pub(crate) fn supervise(self: Arc<Self>, ready: Arc<AtomicBool>) {
+        tokio::spawn(async move {
+            let mut backoff = RESTART_BACKOFF_INITIAL;
+            loop {
+                let started = Instant::now();
+                match self.spawn() {
+                    Ok(mut child) => {
+                        tracing::info!(command = %self.command, pid = ?child.id(), "app process started");
+                        self.pid.send_replace(child.id());
+                        // terminate() may have run between the last check and publishing the pid
+                        if self.stopping.load(Ordering::SeqCst) {
+                            self.terminate();
+                        }
+                        let status = child.wait().await;
+                        self.pid.send_replace(None);
+                        match status {
+                            Ok(status) if self.stopping.load(Ordering::SeqCst) => {
+                                tracing::info!(%status, "app process stopped");
+                            }
+                            Ok(status) => tracing::warn!(%status, "app process exited"),
+                            Err(e) => tracing::error!(error = %e, "failed to wait for app process"),
+                        }
+                    }
+                    Err(e) => tracing::error!(command = %self.command, error = %e, "failed to start app process"),
+                }
+                ready.store(false, Ordering::SeqCst);
+                if self.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                // a process that stayed up longer than the maximum backoff is not crash looping
+                if started.elapsed() > RESTART_BACKOFF_MAX {
+                    backoff = RESTART_BACKOFF_INITIAL;
+                }
+                tracing::info!("restarting app process in {}ms", backoff.as_millis());
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = self.stopped.notified() => {}
+                }
+                if self.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                backoff = (backoff * 2).min(RESTART_BACKOFF_MAX);
+            }
+        });
+    }
+
+    /// Starts the application in its own process group, with its output piped through `tracing`.
+    fn spawn(&self) -> std::io::Result<Child> {
+        let mut child = Command::new("/bin/sh")
+            .arg("-c")
+            .arg(&self.command)
+            .process_group(0)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_output(stdout, "stdout"));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_output(stderr, "stderr"));
+        }
+
+        Ok(child)
+    }
+}
+
+/// Forwards each line of the application's output stream as a `tracing` event.
+async fn forward_output(output: impl AsyncRead + Unpin, stream: &'static str) {
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        tracing::info!(target: "app", stream, "{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_crashed_app_is_restarted() {
+        let marker = std::env::temp_dir().join(format!("lwa-restart-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+
+        let app = Arc::new(AppProcess::new(&format!(
+            "echo started >> {}; exit 1",
+            marker.display()
+        )));
+        let ready = Arc::new(AtomicBool::new(true));
+        app.supervise(ready.clone());
+
+        let mut starts = 0;
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            starts = std::fs::read_to_string(&marker).map(|s| s.lines().count()).unwrap_or(0);
+            if starts >= 2 {
+                break;
+            }
+        }
+
+        assert!(starts >= 2, "app should have been restarted, started {starts} time(s)");
+        assert!(!ready.load(Ordering::SeqCst), "adapter should be marked not ready");
+
+        let _ = std::fs::remove_file(&marker);
+    }
+
+    #[tokio::test]
+    async fn test_terminated_app_is_not_restarted() {
+        let app = Arc::new(AppProcess::new("sleep 30"));
+        app.clone().supervise(Arc::new(AtomicBool::new(true)));
+
+        let mut pid = app.pid.subscribe();
+        tokio::time::timeout(Duration::from_secs(2), pid.wait_for(Option::is_some))
+            .await
+            .expect("app process should start")
+            .unwrap();
+        drop(pid);
+
+        app.terminate();
+        tokio::time::timeout(Duration::from_secs(2), app.exited())
+            .await
+            .expect("app process should exit on SIGTERM");
+
+        // the supervisor would restart a crashed app after 100ms
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(app.pid.borrow().is_none(), "terminated app should not be restarted");
+    }
+
+    #[tokio::test]
+    async fn test_app_terminated_during_backoff_is_not_restarted() {
+        let marker = std::env::temp_dir().join(format!("lwa-backoff-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+
+        let app = Arc::new(AppProcess::new(&format!(
+            "echo started >> {}; exit 1",
+            marker.display()
+        )));
+        app.clone().supervise(Arc::new(AtomicBool::new(true)));
+
+        let starts = || std::fs::read_to_string(&marker).map(|s| s.lines().count()).unwrap_or(0);
+        for _ in 0..50 {
+            if starts() > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(starts(), 1, "app process should start");
+
+        // the app has crashed and the supervisor waits 100ms before restarting it
+        app.exited().await;
+        app.terminate();
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(starts(), 1, "app terminated during the backoff should not be restarted");
+        assert!(app.pid.borrow().is_none());
+
+        let _ = std::fs::remove_file(&marker);
+    }
+}
@@ -1,4 +1,337 @@
-use std::time::Instant;
+use crate::{parse_status_codes, Protocol};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Response};
+use http_body::Body as HttpBody;
+use http_body_util::{BodyExt, Limited};
+use lambda_http::{Body, Error};
+use regex::Regex;
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use url::Url;
+
+/// Largest response body read to validate a readiness check.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Time a readiness command has to exit.
+pub(crate) const EXEC_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A readiness check, configured through `AWS_LWA_READINESS_CHECKS`.
+///
+/// # Examples
+///
+/// ```rust
+/// use lambda_web_adapter::{ReadinessCheck, ReadinessTarget};
+///
+/// let check = ReadinessCheck {
+///     target: ReadinessTarget::Tcp {
+///         host: "127.0.0.1".to_string(),
+///         port: 6379,
+///     },
+///     healthy_status: vec![],
+/// };
+/// assert_eq!(check.to_string(), "tcp://127.0.0.1:6379");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadinessCheck {
+    /// What is checked.
+    pub target: ReadinessTarget,
+    /// Status codes considered healthy for HTTP checks.
+    pub healthy_status: Vec<u16>,
+}
+
+/// What a [`ReadinessCheck`] checks, one variant per [`Protocol`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReadinessTarget {
+    /// Sends a request to the URL, e.g. `http://127.0.0.1:8080/healthz`.
+    Http(Url),
+    /// Connects to the host and port.
+    Tcp {
+        /// Host name or IP address.
+        host: String,
+        /// Port number.
+        port: u16,
+    },
+    /// Runs the command with `/bin/sh -c`, which must exit with status 0.
+    Exec(String),
+    /// Checks that the file exists, e.g. `/tmp/ready`.
+    File(PathBuf),
+}
+
+impl ReadinessCheck {
+    /// The protocol of this check.
+    pub fn protocol(&self) -> Protocol {
+        match self.target {
+            ReadinessTarget::Http(_) => Protocol::Http,
+            ReadinessTarget::Tcp { .. } => Protocol::Tcp,
+            ReadinessTarget::Exec(_) => Protocol::Exec,
+            ReadinessTarget::File(_) => Protocol::File,
+        }
+    }
+}
+
+impl fmt::Display for ReadinessCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            ReadinessTarget::Http(url) => write!(f, "{url}"),
+            ReadinessTarget::Tcp { host, port } => write!(f, "tcp://{host}:{port}"),
+            ReadinessTarget::Exec(command) => write!(f, "exec:{command}"),
+            ReadinessTarget::File(path) => write!(f, "file://{}", path.display()),
+        }
+    }
+}
+
+/// Parses a `;`-separated list of readiness checks.
+///
+/// Each check is a URL, optionally followed by a space and the healthy status
+/// codes in the same format as `AWS_LWA_READINESS_CHECK_HEALTHY_STATUS`, or a
+/// command prefixed with `exec:`:
+///
+/// ```text
+/// http://127.0.0.1:8080/healthz 200-299; tcp://127.0.0.1:6379; file:///tmp/ready; exec:pg_isready -q
+/// ```
+///
+/// HTTP checks without status codes use `default_healthy_status`. Invalid
+/// entries are logged as warnings and skipped.
+pub(crate) fn parse_readiness_checks(input: &str, default_healthy_status: &[u16]) -> Vec<ReadinessCheck> {
+    input
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            if let Some(command) = entry.strip_prefix("exec:") {
+                return Some(ReadinessCheck {
+                    target: ReadinessTarget::Exec(command.trim().to_string()),
+                    healthy_status: default_healthy_status.to_vec(),
+                });
+            }
+
+            let (url, healthy_status) = match entry.split_once(char::is_whitespace) {
+                Some((url, status)) => (url, parse_status_codes(status.trim())),
+                None => (entry, default_healthy_status.to_vec()),
+            };
+            let target = match url.parse::<Url>() {
+                Ok(url) if url.scheme() == "http" => Some(ReadinessTarget::Http(url)),
+                Ok(url) if url.scheme() == "tcp" => match (url.host_str(), url.port()) {
+                    (Some(host), Some(port)) => Some(ReadinessTarget::Tcp {
+                        host: host.to_string(),
+                        port,
+                    }),
+                    _ => None,
+                },
+                Ok(url) if url.scheme() == "file" => url.to_file_path().ok().map(ReadinessTarget::File),
+                _ => None,
+            };
+            if target.is_none() {
+                tracing::warn!("Failed to parse readiness check: {}", entry);
+            }
+            target.map(|target| ReadinessCheck { target, healthy_status })
+        })
+        .collect()
+}
+
+/// Runs a readiness command, healthy when it exits with status 0 within `timeout`.
+///
+/// The command runs with `/bin/sh -c`, without input and with its output discarded.
+/// It is killed if it doesn't exit in time.
+pub(crate) async fn exec_ready(command: &str, timeout: Duration) -> bool {
+    let child = Command::new("/bin/sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn();
+
+    match child {
+        Ok(mut child) => match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(Ok(status)) => status.success(),
+            Ok(Err(e)) => {
+                tracing::debug!(error = %e, "failed to wait for the readiness command");
+                false
+            }
+            Err(_) => {
+                tracing::debug!("readiness command timed out");
+                false
+            }
+        },
+        Err(e) => {
+            tracing::debug!(error = %e, "failed to run the readiness command");
+            false
+        }
+    }
+}
+
+/// How HTTP readiness checks are sent and which responses are healthy,
+/// beyond the status code.
+#[derive(Debug)]
+pub(crate) struct ReadinessProbe {
+    method: Method,
+    headers: HeaderMap,
+    body: Option<BodyMatch>,
+}
+
+impl ReadinessProbe {
+    /// Creates a probe from the `readiness_check_method`, `readiness_check_headers`
+    /// and `readiness_check_body` options.
+    pub(crate) fn new(method: &str, headers: &[(String, String)], body: Option<&str>) -> Result<Self, Error> {
+        let method = Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| Error::from(format!("Invalid readiness check method: {method}")))?;
+
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|_| Error::from(format!("Invalid readiness check header name: {name}")))?;
+            let value = HeaderValue::try_from(value.as_str())
+                .map_err(|_| Error::from(format!("Invalid readiness check header value for {name}")))?;
+            header_map.append(name, value);
+        }
+
+        Ok(ReadinessProbe {
+            method,
+            headers: header_map,
+            body: body.map(BodyMatch::parse).transpose()?,
+        })
+    }
+
+    /// Builds the request sent to `uri`.
+    pub(crate) fn request(&self, uri: http::Uri) -> Request<Body> {
+        let mut request = Request::new(Body::Empty);
+        *request.method_mut() = self.method.clone();
+        *request.uri_mut() = uri;
+        *request.headers_mut() = self.headers.clone();
+        request
+    }
+
+    /// Whether the response has a healthy status and, if configured, a matching body.
+    pub(crate) async fn is_healthy<B>(&self, response: Response<B>, healthy_status: &[u16]) -> bool
+    where
+        B: HttpBody,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        if !healthy_status.contains(&response.status().as_u16()) {
+            return false;
+        }
+        let Some(body_match) = &self.body else {
+            return true;
+        };
+
+        match Limited::new(response.into_body(), MAX_BODY_BYTES).collect().await {
+            Ok(body) => body_match.matches(&body.to_bytes()),
+            Err(e) => {
+                tracing::debug!(error = %e, "failed to read readiness check response body");
+                false
+            }
+        }
+    }
+}
+
+impl Default for ReadinessProbe {
+    fn default() -> Self {
+        ReadinessProbe {
+            method: Method::GET,
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+}
+
+/// Condition on the body of a readiness check response.
+///
+/// - `regex:<pattern>` - the body matches the regular expression
+/// - `json:<pointer> == <value>` - the JSON body has `value` at the JSON pointer,
+///   e.g. `json:/status == "UP"`. `value` is a JSON literal, or else a string
+/// - `contains:<text>`, or any other text - the body contains the text
+#[derive(Debug)]
+enum BodyMatch {
+    Contains(String),
+    Regex(Regex),
+    JsonPointer { pointer: String, value: Value },
+}
+
+impl BodyMatch {
+    fn parse(input: &str) -> Result<Self, Error> {
+        if let Some(pattern) = input.strip_prefix("regex:") {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::from(format!("Invalid readiness check body regex {pattern}: {e}")))?;
+            return Ok(BodyMatch::Regex(regex));
+        }
+
+        if let Some(expression) = input.strip_prefix("json:") {
+            let (pointer, value) = expression
+                .split_once("==")
+                .map(|(pointer, value)| (pointer.trim(), value.trim()))
+                .filter(|(pointer, _)| pointer.is_empty() || pointer.starts_with('/'))
+                .ok_or_else(|| {
+                    Error::from(format!(
+                        "Invalid readiness check body expression {expression}, expected <pointer> == <value>"
+                    ))
+                })?;
+            let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+            return Ok(BodyMatch::JsonPointer {
+                pointer: pointer.to_string(),
+                value,
+            });
+        }
+
+        let text = input.strip_prefix("contains:").unwrap_or(input);
+        Ok(BodyMatch::Contains(text.to_string()))
+    }
+
+    fn matches(&self, body: &[u8]) -> bool {
+        match self {
+            BodyMatch::Contains(text) => String::from_utf8_lossy(body).contains(text.as_str()),
+            BodyMatch::Regex(regex) => regex.is_match(&String::from_utf8_lossy(body)),
+            BodyMatch::JsonPointer { pointer, value } => serde_json::from_slice::<Value>(body)
+                .ok()
+                .is_some_and(|json| json.pointer(pointer) == Some(value)),
+        }
+    }
+}
+
+/// Delays between the attempts of a readiness check.
+///
+/// Starts at `interval` and multiplies it by `multiplier` after each attempt,
+/// up to `max_interval`. A multiplier of `1.0` keeps a fixed interval. The interval is
+/// at least 1ms, so a failing check doesn't spin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Backoff {
+    interval: Duration,
+    multiplier: f64,
+    max_interval: Duration,
+}
+
+impl Backoff {
+    pub(crate) fn new(interval: Duration, multiplier: f64, max_interval: Duration) -> Self {
+        let multiplier = if multiplier.is_finite() && multiplier >= 1.0 {
+            multiplier
+        } else {
+            tracing::warn!("Invalid readiness check backoff multiplier {}, using 1.0", multiplier);
+            1.0
+        };
+        let interval = interval.max(Duration::from_millis(1));
+        Backoff {
+            interval,
+            multiplier,
+            max_interval: max_interval.max(interval),
+        }
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let current = self.interval;
+        self.interval = Duration::try_from_secs_f64(current.as_secs_f64() * self.multiplier)
+            .unwrap_or(self.max_interval)
+            .min(self.max_interval);
+        Some(current)
+    }
+}
 
 pub(crate) struct Checkpoint {
     start: Instant,
@@ -7,10 +340,11 @@ pub(crate) struct Checkpoint {
 }
 
 impl Checkpoint {
-    pub fn new() -> Checkpoint {
-        // The default function timeout is 3 seconds. This will alert the users. See #520
-        let interval_ms = 2000;
-
+    /// Creates a checkpoint that lapses every `interval_ms`.
+    ///
+    /// The default interval is 2 seconds, as the default function timeout is 3 seconds.
+    /// This will alert the users. See #520
+    pub fn new(interval_ms: u128) -> Checkpoint {
         let start = Instant::now();
         Checkpoint {
             start,
@@ -27,8 +361,9 @@ impl Checkpoint {
         self.next_ms += self.interval_ms;
     }
 
-    pub fn lapsed(&self) -> bool {
-        self.start.elapsed().as_millis() >= self.next_ms
+    /// The instant the checkpoint lapses.
+    pub fn deadline(&self) -> Instant {
+        self.start + Duration::from_millis(self.next_ms as u64)
     }
 }
 
@@ -36,19 +371,141 @@ impl Checkpoint {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_readiness_checks() {
+        let checks = parse_readiness_checks(
+            "http://127.0.0.1:8080/healthz 200-201,204; tcp://127.0.0.1:6379 ;file:///tmp/ready; exec: pg_isready -h localhost",
+            &[200],
+        );
+
+        assert_eq!(
+            checks,
+            vec![
+                ReadinessCheck {
+                    target: ReadinessTarget::Http("http://127.0.0.1:8080/healthz".parse().unwrap()),
+                    healthy_status: vec![200, 201, 204],
+                },
+                ReadinessCheck {
+                    target: ReadinessTarget::Tcp {
+                        host: "127.0.0.1".to_string(),
+                        port: 6379,
+                    },
+                    healthy_status: vec![200],
+                },
+                ReadinessCheck {
+                    target: ReadinessTarget::File(PathBuf::from("/tmp/ready")),
+                    healthy_status: vec![200],
+                },
+                ReadinessCheck {
+                    target: ReadinessTarget::Exec("pg_isready -h localhost".to_string()),
+                    healthy_status: vec![200],
+                },
+            ]
+        );
+        assert_eq!(checks[0].to_string(), "http://127.0.0.1:8080/healthz");
+        assert_eq!(checks[1].to_string(), "tcp://127.0.0.1:6379");
+        assert_eq!(checks[2].to_string(), "file:///tmp/ready");
+        assert_eq!(checks[3].to_string(), "exec:pg_isready -h localhost");
+        assert_eq!(checks[3].protocol(), Protocol::Exec);
+    }
+
+    #[test]
+    fn test_parse_readiness_checks_invalid() {
+        // unsupported scheme, tcp without port, not a URL, remote file
+        let checks = parse_readiness_checks(
+            "https://example.com; tcp://127.0.0.1; 127.0.0.1:6379; file://host/ready",
+            &[200],
+        );
+        assert!(checks.is_empty());
+        assert!(parse_readiness_checks("", &[200]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exec_ready() {
+        let timeout = Duration::from_secs(1);
+        assert!(exec_ready("true", timeout).await);
+        assert!(!exec_ready("exit 3", timeout).await);
+        assert!(!exec_ready("sleep 5", Duration::from_millis(50)).await);
+    }
+
+    #[test]
+    fn test_body_match() {
+        let contains = BodyMatch::parse("ready").unwrap();
+        assert!(contains.matches(b"app is ready"));
+        assert!(!contains.matches(b"warming up"));
+        assert!(BodyMatch::parse("contains:regex:").unwrap().matches(b"regex: literal"));
+
+        let regex = BodyMatch::parse(r"regex:^\{.*\}$").unwrap();
+        assert!(regex.matches(b"{}"));
+        assert!(!regex.matches(b"<html>"));
+
+        let json = BodyMatch::parse(r#"json:/status == "UP""#).unwrap();
+        assert!(json.matches(br#"{"status":"UP","components":{}}"#));
+        assert!(!json.matches(br#"{"status":"DOWN"}"#));
+        assert!(!json.matches(b"UP"));
+
+        // values that aren't JSON literals are compared as strings
+        let json = BodyMatch::parse("json:/components/db/status==UP").unwrap();
+        assert!(json.matches(br#"{"components":{"db":{"status":"UP"}}}"#));
+        let json = BodyMatch::parse("json:/ready == true").unwrap();
+        assert!(json.matches(br#"{"ready":true}"#));
+        assert!(!json.matches(br#"{"ready":"true"}"#));
+    }
+
+    #[test]
+    fn test_body_match_invalid() {
+        assert!(BodyMatch::parse("regex:(").is_err());
+        assert!(BodyMatch::parse("json:/status").is_err());
+        assert!(BodyMatch::parse(r#"json:status == "UP""#).is_err());
+    }
+
+    #[test]
+    fn test_readiness_probe() {
+        let probe = ReadinessProbe::new(
+            "head",
+            &[("Authorization".to_string(), "Bearer token".to_string())],
+            None,
+        )
+        .unwrap();
+        let request = probe.request("http://127.0.0.1:8080/healthz".parse().unwrap());
+        assert_eq!(request.method(), Method::HEAD);
+        assert_eq!(request.headers()["authorization"], "Bearer token");
+
+        assert!(ReadinessProbe::new("GET", &[("bad header".to_string(), "x".to_string())], None).is_err());
+        assert!(ReadinessProbe::new("GET", &[], Some("regex:(")).is_err());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff::new(Duration::from_millis(10), 2.0, Duration::from_millis(50));
+        let delays: Vec<_> = backoff.take(5).map(|delay| delay.as_millis()).collect();
+        assert_eq!(delays, vec![10, 20, 40, 50, 50]);
+
+        // invalid multipliers keep a fixed interval
+        let backoff = Backoff::new(Duration::from_millis(10), 0.5, Duration::from_millis(50));
+        let delays: Vec<_> = backoff.take(3).map(|delay| delay.as_millis()).collect();
+        assert_eq!(delays, vec![10, 10, 10]);
+
+        // a zero interval would retry in a busy loop
+        let backoff = Backoff::new(Duration::ZERO, 1.0, Duration::ZERO);
+        let delays: Vec<_> = backoff.take(3).map(|delay| delay.as_millis()).collect();
+        assert_eq!(delays, vec![1, 1, 1]);
+    }
+
     #[test]
     fn test_checkpoint_new() {
-        let checkpoint = Checkpoint::new();
+        let checkpoint = Checkpoint::new(2000);
         assert_eq!(checkpoint.next_ms(), 2000);
-        assert!(!checkpoint.lapsed());
+        assert!(checkpoint.deadline() > Instant::now());
     }
 
     #[test]
     fn test_checkpoint_increment() {
-        let mut checkpoint = Checkpoint::new();
+        let mut checkpoint = Checkpoint::new(2000);
         checkpoint.increment();
         assert_eq!(checkpoint.next_ms(), 4000);
-        assert!(!checkpoint.lapsed());
+        assert!(checkpoint.deadline() > Instant::now());
+        assert_eq!(checkpoint.deadline(), checkpoint.start + Duration::from_secs(4));
     }
 
     #[test]
@@ -58,6 +515,6 @@ mod tests {
             interval_ms: 0,
             next_ms: 0,
         };
-        assert!(checkpoint.lapsed());
+        assert!(checkpoint.deadline() <= Instant::now());
     }
 }
@@ -0,0 +1,189 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::HeaderRule;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::fmt;
+
+/// Headers that are always redacted from the adapter's logs.
+const DEFAULT_REDACTED_HEADERS: [&str; 6] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-amz-security-token",
+];
+
+/// Request context fields that are redacted from the `x-amzn-request-context` header:
+/// the API key, IAM access key, and the claims and context set by authorizers.
+const REDACTED_CONTEXT_FIELDS: [&str; 3] = ["/identity/apiKey", "/identity/accessKey", "/authorizer"];
+
+/// Value logged in place of a redacted header value.
+const REDACTED: &str = "[REDACTED]";
+
+/// Names of the headers whose values are hidden from the adapter's logs.
+#[derive(Clone, Debug)]
+pub(crate) struct Redaction {
+    headers: Vec<HeaderName>,
+    /// Prefixes of the context headers carrying claims and authorizer context.
+    prefixes: Vec<String>,
+}
+
+impl Redaction {
+    /// Creates a redaction of the default headers, the context headers carrying claims
+    /// and authorizer context under `context_header_prefix`, and the given headers.
+    ///
+    /// Invalid header names are logged as warnings and skipped.
+    pub(crate) fn new<'a>(headers: impl IntoIterator<Item = &'a str>, context_header_prefix: &str) -> Self {
+        let mut names: Vec<HeaderName> = DEFAULT_REDACTED_HEADERS
+            .iter()
+            .map(|name| HeaderName::from_static(name))
+            .collect();
+        for name in headers {
+            match HeaderName::try_from(name.trim()) {
+                Ok(name) if !names.contains(&name) => names.push(name),
+                Ok(_) => {}
+                Err(_) => tracing::warn!("Invalid header name to redact: {}", name),
+            }
+        }
+        Redaction {
+            headers: names,
+            prefixes: vec![
+                format!("{context_header_prefix}claim-"),
+                format!("{context_header_prefix}authorizer-"),
+            ],
+        }
+    }
+
+    /// Also redacts the headers that request header `rules` fill with redacted values:
+    /// `set` rules with request context placeholders, and `rename` rules from a redacted header.
+    pub(crate) fn with_header_rules(mut self, rules: &[HeaderRule]) -> Self {
+        for rule in rules {
+            let name = match rule {
+                HeaderRule::Set { name, value } if value.contains("${") => name,
+                HeaderRule::Rename { from, to } if self.redacts(from) => to,
+                _ => continue,
+            };
+            if !self.redacts(name) {
+                self.headers.push(name.clone());
+            }
+        }
+        self
+    }
+
+    fn redacts(&self, name: &HeaderName) -> bool {
+        self.headers.contains(name) || self.prefixes.iter().any(|prefix| name.as_str().starts_with(prefix))
+    }
+
+    /// Wraps `headers` to log them with the values of redacted headers hidden.
+    pub(crate) fn headers<'a>(&'a self, headers: &'a HeaderMap) -> RedactedHeaders<'a> {
+        RedactedHeaders {
+            headers,
+            redaction: self,
+        }
+    }
+}
+
+/// A header map that formats like [`HeaderMap`], with the values of redacted headers hidden.
+pub(crate) struct RedactedHeaders<'a> {
+    headers: &'a HeaderMap,
+    redaction: &'a Redaction,
+}
+
+impl fmt::Debug for RedactedHeaders<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for (name, value) in self.headers {
+            if self.redaction.redacts(name) {
+                map.entry(name, &REDACTED);
+            } else if name == "x-amzn-request-context" {
+                map.entry(name, &redacted_request_context(value));
+            } else {
+                map.entry(name, value);
+            }
+        }
+        map.finish()
+    }
+}
+
+/// The request context JSON with [`REDACTED_CONTEXT_FIELDS`] hidden, or [`REDACTED`] if
+/// it can't be parsed.
+fn redacted_request_context(value: &HeaderValue) -> String {
+    let Ok(mut context) = serde_json::from_slice::<Value>(value.as_bytes()) else {
+        return REDACTED.to_string();
+    };
+    for pointer in REDACTED_CONTEXT_FIELDS {
+        if let Some(field) = context.pointer_mut(pointer).filter(|field| !field.is_null()) {
+            *field = Value::from(REDACTED);
+        }
+    }
+    context.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn test_redacted_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer secret"));
+        headers.insert("cookie", HeaderValue::from_static("session=secret"));
+        headers.insert("x-auth-token", HeaderValue::from_static("secret"));
+        headers.insert("accept", HeaderValue::from_static("application/json"));
+
+        let redaction = Redaction::new(["X-Auth-Token", "not a header"], "x-amzn-");
+        let logged = format!("{:?}", redaction.headers(&headers));
+
+        assert!(!logged.contains("secret"), "unexpected secret in {logged}");
+        assert_eq!(
+            logged,
+            r#"{"authorization": "[REDACTED]", "cookie": "[REDACTED]", "x-auth-token": "[REDACTED]", "accept": "application/json"}"#
+        );
+    }
+
+    #[test]
+    fn test_redacted_request_context() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-amzn-request-context",
+            HeaderValue::from_static(
+                r#"{"stage":"prod","identity":{"apiKey":"secret-api-key","sourceIp":"203.0.113.1"},"authorizer":{"claims":{"sub":"secret-sub"}}}"#,
+            ),
+        );
+        headers.insert("x-amzn-claim-sub", HeaderValue::from_static("secret-sub"));
+        headers.insert("x-amzn-authorizer-tenant", HeaderValue::from_static("secret-tenant"));
+        headers.insert("x-amzn-source-ip", HeaderValue::from_static("203.0.113.1"));
+
+        let logged = format!("{:?}", Redaction::new([], "x-amzn-").headers(&headers));
+
+        assert!(!logged.contains("secret"), "unexpected secret in {logged}");
+        assert!(logged.contains(r#"\"apiKey\":\"[REDACTED]\""#), "{logged}");
+        assert!(logged.contains(r#"\"authorizer\":\"[REDACTED]\""#), "{logged}");
+        assert!(logged.contains(r#"\"sourceIp\":\"203.0.113.1\""#), "{logged}");
+        assert!(logged.contains(r#""x-amzn-source-ip": "203.0.113.1""#), "{logged}");
+    }
+
+    #[test]
+    fn test_redacted_header_rules() {
+        let rules = crate::header_rules::parse_header_rules(
+            "set x-user-id: ${/authorizer/jwt/claims/sub}; set x-env: production; \
+             rename authorization x-legacy-token; rename x-legacy-token x-old-token; rename x-trace x-request-id",
+        );
+        let redaction = Redaction::new([], "x-amzn-").with_header_rules(&rules);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-id", HeaderValue::from_static("secret-sub"));
+        headers.insert("x-old-token", HeaderValue::from_static("secret-token"));
+        headers.insert("x-env", HeaderValue::from_static("production"));
+        headers.insert("x-request-id", HeaderValue::from_static("abc"));
+        let logged = format!("{:?}", redaction.headers(&headers));
+
+        assert!(!logged.contains("secret"), "unexpected secret in {logged}");
+        assert!(logged.contains(r#""x-env": "production""#), "{logged}");
+        assert!(logged.contains(r#""x-request-id": "abc""#), "{logged}");
+    }
+}
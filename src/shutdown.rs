@@ -0,0 +1,98 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tokio::sync::watch;
+
+/// Tracks in-flight invocations so that shutdown can wait for them to finish.
+#[derive(Debug)]
+pub(crate) struct InFlight {
+    closed: AtomicBool,
+    count: watch::Sender<usize>,
+}
+
+impl InFlight {
+    pub(crate) fn new() -> Self {
+        InFlight {
+            closed: AtomicBool::new(false),
+            count: watch::Sender::new(0),
+        }
+    }
+
+    /// Registers a new invocation, or returns `None` once the adapter stopped accepting them.
+    ///
+    /// The invocation counts as in flight until the returned guard is dropped.
+    pub(crate) fn enter(self: &Arc<Self>) -> Option<InFlightGuard> {
+        // count first, so that a concurrent `close` either sees this invocation or rejects it
+        self.count.send_modify(|count| *count += 1);
+        let guard = InFlightGuard(self.clone());
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Stops accepting new invocations.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns the number of invocations currently in flight.
+    pub(crate) fn count(&self) -> usize {
+        *self.count.borrow()
+    }
+
+    /// Waits until no invocation is in flight.
+    pub(crate) async fn drained(&self) {
+        let mut count = self.count.subscribe();
+        // the sender lives as long as `self`, so this cannot fail
+        let _ = count.wait_for(|count| *count == 0).await;
+    }
+}
+
+/// Marks an invocation as in flight until dropped.
+#[derive(Debug)]
+pub(crate) struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.count.send_modify(|count| *count -= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_close_rejects_new_invocations() {
+        let in_flight = Arc::new(InFlight::new());
+        assert!(in_flight.enter().is_some());
+
+        in_flight.close();
+
+        assert!(in_flight.enter().is_none());
+        assert_eq!(in_flight.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_drained_waits_for_in_flight_invocations() {
+        let in_flight = Arc::new(InFlight::new());
+        let guard = in_flight.enter().unwrap();
+        in_flight.close();
+
+        assert!(tokio::time::timeout(Duration::from_millis(50), in_flight.drained())
+            .await
+            .is_err());
+
+        drop(guard);
+
+        assert!(tokio::time::timeout(Duration::from_millis(50), in_flight.drained())
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bytes::Bytes;
+use http::{header::CONTENT_TYPE, HeaderValue, Method, Request, Response};
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioIo;
+use lambda_http::{Body, Error};
+use serde_json::Value;
+use std::future::Future;
+use tokio::net::TcpListener;
+
+/// Telemetry API schema version used for the subscription.
+const SCHEMA_VERSION: &str = "2022-12-13";
+
+/// Subscribes the extension to the Telemetry API.
+///
+/// Lambda delivers batches of the given event `types` to the listener on `port`,
+/// which must already be accepting connections.
+pub(crate) async fn subscribe(
+    client: &Client<HttpConnector, Body>,
+    aws_lambda_runtime_api: &str,
+    extension_id: &HeaderValue,
+    types: &[&str],
+    port: u16,
+) -> Result<(), Error> {
+    let subscription = serde_json::json!({
+        "schemaVersion": SCHEMA_VERSION,
+        "types": types,
+        "buffering": { "maxItems": 1000, "maxBytes": 262144, "timeoutMs": 100 },
+        "destination": { "protocol": "HTTP", "URI": format!("http://sandbox.localdomain:{port}") },
+    });
+
+    let subscribe_req = Request::builder()
+        .method(Method::PUT)
+        .uri(format!("http://{aws_lambda_runtime_api}/2022-07-01/telemetry"))
+        .header("Lambda-Extension-Identifier", extension_id)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(subscription.to_string()))?;
+
+    let subscribe_res = client.request(subscribe_req).await?;
+    let status = subscribe_res.status();
+    if !status.is_success() {
+        let body = subscribe_res.into_body().collect().await?.to_bytes();
+        return Err(Error::from(format!(
+            "Telemetry API subscription failed with status {}: {}",
+            status,
+            String::from_utf8_lossy(&body)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Spawns a task that accepts Telemetry API deliveries on `listener`.
+///
+/// Each delivered batch, a JSON array of events, is passed to `on_batch`. Lambda
+/// waits for the response, so a slow `on_batch` slows down deliveries instead of
+/// piling them up in memory.
+pub(crate) fn serve<F, Fut>(listener: TcpListener, on_batch: F)
+where
+    F: Fn(Bytes) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to accept telemetry connection");
+                    continue;
+                }
+            };
+
+            let on_batch = on_batch.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |req: Request<Incoming>| {
+                    let on_batch = on_batch.clone();
+                    async move {
+                        let batch = req.into_body().collect().await?.to_bytes();
+                        on_batch(batch).await;
+                        Ok::<_, hyper::Error>(Response::new(Empty::<Bytes>::new()))
+                    }
+                });
+
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    tracing::debug!(error = %e, "telemetry connection closed with error");
+                }
+            });
+        }
+    });
+}
+
+/// Re-emits a batch of Telemetry API events as `tracing` events with the `telemetry` target.
+pub(crate) fn trace_batch(batch: &[u8]) {
+    match serde_json::from_slice::<Vec<Value>>(batch) {
+        Ok(events) => events.iter().for_each(trace_event),
+        Err(e) => tracing::warn!(error = %e, "failed to parse telemetry batch"),
+    }
+}
+
+fn trace_event(event: &Value) {
+    let record = &event["record"];
+    let metrics = &record["metrics"];
+    let request_id = record["requestId"].as_str();
+    let status = record["status"].as_str();
+
+    match event["type"].as_str().unwrap_or_default() {
+        "platform.initReport" => tracing::info!(
+            target: "telemetry",
+            initialization_type = record["initializationType"].as_str(),
+            phase = record["phase"].as_str(),
+            status,
+            duration_ms = metrics["durationMs"].as_f64(),
+            "platform.initReport"
+        ),
+        "platform.runtimeDone" => tracing::info!(
+            target: "telemetry",
+            request_id,
+            status,
+            duration_ms = metrics["durationMs"].as_f64(),
+            produced_bytes = metrics["producedBytes"].as_u64(),
+            "platform.runtimeDone"
+        ),
+        "platform.report" => tracing::info!(
+            target: "telemetry",
+            request_id,
+            status,
+            duration_ms = metrics["durationMs"].as_f64(),
+            billed_duration_ms = metrics["billedDurationMs"].as_u64(),
+            memory_size_mb = metrics["memorySizeMB"].as_u64(),
+            max_memory_used_mb = metrics["maxMemoryUsedMB"].as_u64(),
+            init_duration_ms = metrics["initDurationMs"].as_f64(),
+            "platform.report"
+        ),
+        "function" => match record.as_str() {
+            Some(line) => tracing::info!(target: "telemetry", "{}", line.trim_end()),
+            None => tracing::info!(target: "telemetry", "{}", record),
+        },
+        event_type => tracing::debug!(target: "telemetry", event_type, %record, "telemetry event"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::PUT, MockServer};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let runtime_api = MockServer::start();
+        let subscription = runtime_api.mock(|when, then| {
+            when.method(PUT)
+                .path("/2022-07-01/telemetry")
+                .header("Lambda-Extension-Identifier", "ext-id")
+                .json_body_includes(r#"{ "types": ["platform", "function"] }"#)
+                .json_body_includes(
+                    r#"{ "destination": { "protocol": "HTTP", "URI": "http://sandbox.localdomain:4243" } }"#,
+                );
+            then.status(200);
+        });
+
+        let client = Client::builder(hyper_util::rt::TokioExecutor::new()).build(HttpConnector::new());
+        subscribe(
+            &client,
+            &runtime_api.address().to_string(),
+            &HeaderValue::from_static("ext-id"),
+            &["platform", "function"],
+            4243,
+        )
+        .await
+        .expect("subscription should succeed");
+
+        subscription.assert();
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_failure() {
+        let runtime_api = MockServer::start();
+        runtime_api.mock(|when, then| {
+            when.method(PUT).path("/2022-07-01/telemetry");
+            then.status(400).body("ValidationError");
+        });
+
+        let client = Client::builder(hyper_util::rt::TokioExecutor::new()).build(HttpConnector::new());
+        let result = subscribe(
+            &client,
+            &runtime_api.address().to_string(),
+            &HeaderValue::from_static("ext-id"),
+            &["platform"],
+            4243,
+        )
+        .await;
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("400"), "unexpected error: {error}");
+        assert!(error.contains("ValidationError"), "unexpected error: {error}");
+    }
+
+    #[tokio::test]
+    async fn test_serve_delivers_batches() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        serve(listener, move |batch| {
+            let tx = tx.clone();
+            async move {
+                tx.send(batch).unwrap();
+            }
+        });
+
+        let batch = r#"[{"time":"2024-01-01T00:00:00.000Z","type":"platform.report","record":{"requestId":"abc"}}]"#;
+        let client = Client::builder(hyper_util::rt::TokioExecutor::new()).build(HttpConnector::new());
+        let res = client
+            .request(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(format!("http://{address}/"))
+                    .body(Body::from(batch))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(rx.recv().await.unwrap(), Bytes::from(batch));
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Translates an X-Ray trace header into a W3C `traceparent` header value.
+///
+/// `Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1`
+/// becomes `00-5759e988bd862e3fe1be46a994272793-53995c3f42cd8ad8-01`.
+///
+/// Returns `None` if the header has no valid `Root` or `Parent`. A missing or
+/// undecided (`Sampled=?`) sampling decision maps to "not sampled".
+pub(crate) fn traceparent(xray_trace_header: &str) -> Option<String> {
+    let mut trace_id = None;
+    let mut parent_id = None;
+    let mut sampled = false;
+
+    for field in xray_trace_header.split(';') {
+        match field.trim().split_once('=') {
+            Some(("Root", root)) => trace_id = root_to_trace_id(root),
+            Some(("Parent", parent)) if is_lower_hex(parent, 16) && parent != "0000000000000000" => {
+                parent_id = Some(parent)
+            }
+            Some(("Sampled", flag)) => sampled = flag == "1",
+            _ => {}
+        }
+    }
+
+    let flags = if sampled { "01" } else { "00" };
+    Some(format!("00-{}-{}-{}", trace_id?, parent_id?, flags))
+}
+
+/// Converts an X-Ray root (`1-<8 hex epoch>-<24 hex random>`) into a 32 hex digit W3C trace id.
+fn root_to_trace_id(root: &str) -> Option<String> {
+    match root.split('-').collect::<Vec<_>>().as_slice() {
+        ["1", epoch, random] if is_lower_hex(epoch, 8) && is_lower_hex(random, 24) => {
+            let trace_id = format!("{epoch}{random}");
+            (trace_id != "0".repeat(32)).then_some(trace_id)
+        }
+        _ => None,
+    }
+}
+
+fn is_lower_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traceparent_from_xray_header() {
+        assert_eq!(
+            traceparent("Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1"),
+            Some("00-5759e988bd862e3fe1be46a994272793-53995c3f42cd8ad8-01".to_string())
+        );
+        assert_eq!(
+            traceparent("Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=0;Lineage=a:1"),
+            Some("00-5759e988bd862e3fe1be46a994272793-53995c3f42cd8ad8-00".to_string())
+        );
+        assert_eq!(
+            traceparent("Sampled=?;Parent=53995c3f42cd8ad8;Root=1-5759e988-bd862e3fe1be46a994272793"),
+            Some("00-5759e988bd862e3fe1be46a994272793-53995c3f42cd8ad8-00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_traceparent_invalid_xray_header() {
+        // no parent
+        assert_eq!(traceparent("Root=1-5759e988-bd862e3fe1be46a994272793;Sampled=1"), None);
+        // malformed root
+        assert_eq!(
+            traceparent("Root=2-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8"),
+            None
+        );
+        assert_eq!(traceparent("Root=1-5759e988;Parent=53995c3f42cd8ad8"), None);
+        // malformed parent
+        assert_eq!(
+            traceparent("Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995C3F42CD8AD8"),
+            None
+        );
+        assert_eq!(traceparent(""), None);
+    }
+}
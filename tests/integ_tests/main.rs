@@ -6,17 +6,19 @@ use std::io::prelude::*;
 use std::sync::Arc;
 
 use crate::common::LambdaEventBuilder;
-use http::HeaderMap;
 use http::Request;
+use http::{HeaderMap, HeaderName};
 use http::{Method, Response};
 use httpmock::{
     Method::{DELETE, GET, POST, PUT},
     MockServer,
 };
-use hyper::body::Incoming;
 use lambda_http::Body;
 use lambda_http::Context;
-use lambda_web_adapter::{Adapter, AdapterOptions, LambdaInvokeMode, Protocol};
+use lambda_web_adapter::{
+    Adapter, AdapterBody, AdapterOptions, EventSource, ForwardedHeaders, HeaderRule, LambdaInvokeMode, Protocol,
+    RequestTimeoutAction, TracePropagation, UpstreamProtocol,
+};
 use tower::{Service, ServiceBuilder};
 
 use flate2::read::GzDecoder;
@@ -136,12 +138,49 @@ async fn test_http_readiness_check() {
 
     // Initialize adapter and do readiness check
     let mut adapter = Adapter::new(&options).expect("Failed to create adapter");
-    adapter.check_init_health().await;
+    adapter.check_init_health().await.unwrap();
 
     // Assert app server's healthcheck endpoint got called
     healthcheck.assert();
 }
 
+#[tokio::test]
+async fn test_readiness_check_timeout() {
+    // Start app server that never becomes healthy
+    let app_server = MockServer::start();
+    let healthcheck = app_server.mock(|when, then| {
+        when.method(GET).path("/healthcheck");
+        then.status(503);
+    });
+
+    // Prepare adapter configuration
+    let options = AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        readiness_check_healthy_status: vec![200],
+        readiness_check_interval_ms: 10,
+        readiness_check_backoff: 2.0,
+        readiness_check_max_interval_ms: 40,
+        readiness_check_timeout_ms: Some(200),
+        ..Default::default()
+    };
+
+    // Initialize adapter and do readiness check
+    let mut adapter = Adapter::new(&options).expect("Failed to create adapter");
+    let error = adapter.check_init_health().await.unwrap_err().to_string();
+
+    assert!(
+        error.contains("app is not ready after 200ms"),
+        "unexpected error: {error}"
+    );
+    assert!(error.contains("/healthcheck"), "unexpected error: {error}");
+    // 10 + 20 + 40 + 40 + 40 + ... ms between attempts within 200ms
+    let attempts = healthcheck.calls();
+    assert!((4..=8).contains(&attempts), "unexpected number of attempts: {attempts}");
+}
+
 #[tokio::test]
 async fn test_http_basic_request() {
     // Start app server
@@ -1051,6 +1090,428 @@ async fn test_http_authorization_source_missing_header() {
     assert_eq!("No Auth", body_to_string(response).await);
 }
 
+#[tokio::test]
+async fn test_http_request_header_rules() {
+    // Start app server
+    let app_server = MockServer::start();
+    let test_endpoint = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .header("x-legacy-token", "token")
+            .header("x-target-group", "arn:aws:us-east-1:123456789:elb/Foo")
+            .header_missing("x-forwarded-for")
+            .header_missing("x-api-token");
+        then.status(200).body("Hello World");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        request_header_rules: vec![
+            HeaderRule::Drop("x-forwarded-*".to_string()),
+            HeaderRule::Rename {
+                from: HeaderName::from_static("x-api-token"),
+                to: HeaderName::from_static("x-legacy-token"),
+            },
+            HeaderRule::Set {
+                name: HeaderName::from_static("x-target-group"),
+                value: "${/elb/targetGroupArn}".to_string(),
+            },
+        ],
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let req = LambdaEventBuilder::new()
+        .with_path("/hello")
+        .with_header("x-forwarded-for", "203.0.113.1")
+        .with_header("x-api-token", "token")
+        .with_header("x-target-group", "spoofed")
+        .build();
+    let mut request = Request::from(req);
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    test_endpoint.assert();
+    assert_eq!(200, response.status());
+}
+
+#[tokio::test]
+async fn test_http_request_header_rules_keep_adapter_headers() {
+    let app_server = MockServer::start();
+    let test_endpoint = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .header("accept", "text/plain")
+            .header_exists("x-amzn-request-context")
+            .header_exists("x-amzn-lambda-context")
+            .header("x-forwarded-for", "203.0.113.1")
+            .header("x-forwarded-proto", "https")
+            .header("x-forwarded-host", "example.com")
+            .header_missing("x-client-header");
+        then.status(200).body("Hello World");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        request_header_rules: vec![HeaderRule::Allow("accept".to_string())],
+        forwarded_headers: ForwardedHeaders::Append,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    // the client forged 198.51.100.1, the ALB appended the address it saw
+    let req = LambdaEventBuilder::new()
+        .with_path("/hello")
+        .with_header("accept", "text/plain")
+        .with_header("host", "example.com")
+        .with_header("x-forwarded-for", "198.51.100.1, 203.0.113.1")
+        .with_header("x-forwarded-proto", "https")
+        .with_header("x-client-header", "value")
+        .build();
+    let mut request = Request::from(req);
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    test_endpoint.assert();
+    assert_eq!(200, response.status());
+}
+
+#[tokio::test]
+async fn test_pass_through_routes() {
+    // Start app server
+    let app_server = MockServer::start();
+    let sqs_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events/sqs")
+            .header("x-amzn-event-source", "sqs");
+        then.status(200).body("sqs");
+    });
+    let eventbridge_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events/eb/Scheduled%20Event")
+            .header("x-amzn-event-source", "eventbridge");
+        then.status(200).body("eventbridge");
+    });
+    let default_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events")
+            .header("x-amzn-event-source", "unknown");
+        then.status(200).body("unknown");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        pass_through_routes: vec![
+            (EventSource::Sqs, "/events/sqs".to_string()),
+            (EventSource::EventBridge, "/events/eb/{detail-type}".to_string()),
+        ],
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    for (event, expected) in [
+        (
+            json!({ "Records": [{ "messageId": "1", "eventSource": "aws:sqs", "body": "hello" }] }),
+            "sqs",
+        ),
+        (
+            json!({ "source": "aws.events", "detail-type": "Scheduled Event", "detail": {} }),
+            "eventbridge",
+        ),
+        (json!({ "hello": "world" }), "unknown"),
+    ] {
+        let mut request = lambda_http::request::from_str(&event.to_string()).expect("Failed to parse event");
+        add_lambda_context_to_request(&mut request);
+
+        let response = adapter.call(request).await.expect("Request failed");
+        assert_eq!(200, response.status());
+        assert_eq!(expected, body_to_string(response).await);
+    }
+
+    sqs_endpoint.assert();
+    eventbridge_endpoint.assert();
+    default_endpoint.assert();
+}
+
+#[tokio::test]
+async fn test_sqs_fan_out() {
+    // Start app server
+    let app_server = MockServer::start();
+    let ok_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events")
+            .header("content-type", "application/json")
+            .header("x-amzn-sqs-receive-count", "1")
+            .json_body(json!({ "order": 1 }));
+        then.status(202);
+    });
+    let failing_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events")
+            .header("content-type", "text/plain; charset=utf-8")
+            .body("poison");
+        then.status(500);
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        fan_out: vec![EventSource::Sqs],
+        fan_out_concurrency: 2,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let record = |id: &str, body: &str| {
+        json!({
+            "messageId": id,
+            "body": body,
+            "attributes": { "ApproximateReceiveCount": "1" },
+            "eventSource": "aws:sqs",
+            "eventSourceARN": "arn:aws:sqs:us-east-1:123456789012:orders"
+        })
+    };
+    let event = json!({
+        "Records": [
+            record("1", "{\"order\":1}"),
+            record("2", "poison"),
+            record("3", "{\"order\":1}"),
+        ]
+    });
+    let mut request = lambda_http::request::from_str(&event.to_string()).expect("Failed to parse event");
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+    assert_eq!(200, response.status());
+    assert_eq!(
+        json!({ "batchItemFailures": [{ "itemIdentifier": "2" }] }).to_string(),
+        body_to_string(response).await
+    );
+
+    ok_endpoint.assert_calls(2);
+    failing_endpoint.assert();
+}
+
+#[tokio::test]
+async fn test_kinesis_fan_out() {
+    // Start app server
+    let app_server = MockServer::start();
+    let ok_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events")
+            .header("x-amzn-kinesis-sequence-number", "1")
+            .json_body(json!({ "order": 1 }));
+        then.status(200);
+    });
+    let failing_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events")
+            .header("x-amzn-kinesis-sequence-number", "2");
+        then.status(500);
+    });
+    let skipped_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events")
+            .header("x-amzn-kinesis-sequence-number", "3");
+        then.status(200);
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        fan_out: vec![EventSource::Kinesis],
+        fan_out_concurrency: 4,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let record = |sequence_number: &str| {
+        json!({
+            "kinesis": { "partitionKey": "order", "sequenceNumber": sequence_number, "data": "eyJvcmRlciI6MX0=" },
+            "eventSource": "aws:kinesis",
+            "eventSourceARN": "arn:aws:kinesis:us-east-1:123456789012:stream/orders"
+        })
+    };
+    let event = json!({ "Records": [record("1"), record("2"), record("3")] });
+    let mut request = lambda_http::request::from_str(&event.to_string()).expect("Failed to parse event");
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+    assert_eq!(200, response.status());
+    assert_eq!(
+        json!({ "batchItemFailures": [{ "itemIdentifier": "2" }] }).to_string(),
+        body_to_string(response).await
+    );
+
+    ok_endpoint.assert();
+    failing_endpoint.assert();
+    skipped_endpoint.assert_calls(0);
+}
+
+#[tokio::test]
+async fn test_unwrap_events() {
+    // Start app server
+    let app_server = MockServer::start();
+    let sns_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events")
+            .header("content-type", "text/plain; charset=utf-8")
+            .header("x-amzn-sns-topic-arn", "arn:aws:sns:us-east-1:123456789012:orders")
+            .header("x-amzn-sns-attribute-tenant", "acme")
+            .body("order 42 created");
+        then.status(200).body("sns");
+    });
+    let eventbridge_endpoint = app_server.mock(|when, then| {
+        when.method(POST)
+            .path("/events/eb/Scheduled%20Event")
+            .header("content-type", "application/json")
+            .header("x-amzn-eventbridge-source", "aws.events")
+            .json_body(json!({ "job": "cleanup" }));
+        then.status(200).body("eventbridge");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        pass_through_routes: vec![(EventSource::EventBridge, "/events/eb/{detail-type}".to_string())],
+        unwrap_events: vec![EventSource::Sns, EventSource::EventBridge],
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    for (event, expected) in [
+        (
+            json!({
+                "Records": [{
+                    "EventSource": "aws:sns",
+                    "Sns": {
+                        "TopicArn": "arn:aws:sns:us-east-1:123456789012:orders",
+                        "Message": "order 42 created",
+                        "MessageAttributes": { "Tenant": { "Type": "String", "Value": "acme" } }
+                    }
+                }]
+            }),
+            "sns",
+        ),
+        (
+            json!({ "source": "aws.events", "detail-type": "Scheduled Event", "detail": { "job": "cleanup" } }),
+            "eventbridge",
+        ),
+    ] {
+        let mut request = lambda_http::request::from_str(&event.to_string()).expect("Failed to parse event");
+        add_lambda_context_to_request(&mut request);
+
+        let response = adapter.call(request).await.expect("Request failed");
+        assert_eq!(200, response.status());
+        assert_eq!(expected, body_to_string(response).await);
+    }
+
+    sns_endpoint.assert();
+    eventbridge_endpoint.assert();
+}
+
+#[tokio::test]
+async fn test_s3_fan_out() {
+    // Start app server
+    let app_server = MockServer::start();
+    let report_endpoint = app_server.mock(|when, then| {
+        when.method(PUT)
+            .path("/objects/uploads/reports/q1%202024.csv")
+            .header("x-amzn-s3-event-name", "ObjectCreated:Put")
+            .header("x-amzn-s3-object-size", "42");
+        then.status(204);
+    });
+    let missing_endpoint = app_server.mock(|when, then| {
+        when.method(PUT).path("/objects/uploads/missing.csv");
+        then.status(404);
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        fan_out: vec![EventSource::S3],
+        s3_object_route: "PUT /objects/{bucket}/{key}".to_string(),
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let record = |key: &str| {
+        json!({
+            "eventSource": "aws:s3",
+            "eventName": "ObjectCreated:Put",
+            "s3": { "bucket": { "name": "uploads" }, "object": { "key": key, "size": 42 } }
+        })
+    };
+    let invoke = |event: serde_json::Value| {
+        let mut request = lambda_http::request::from_str(&event.to_string()).expect("Failed to parse event");
+        add_lambda_context_to_request(&mut request);
+        request
+    };
+
+    let response = adapter
+        .call(invoke(json!({ "Records": [record("reports/q1+2024.csv")] })))
+        .await
+        .expect("Request failed");
+    assert_eq!(200, response.status());
+    assert_eq!(
+        json!({ "results": [{ "itemIdentifier": "s3://uploads/reports/q1 2024.csv", "statusCode": 204 }] }).to_string(),
+        body_to_string(response).await
+    );
+
+    // the invocation fails if any object fails
+    let result = adapter
+        .call(invoke(
+            json!({ "Records": [record("reports/q1+2024.csv"), record("missing.csv")] }),
+        ))
+        .await;
+    assert_eq!(
+        json!({
+            "results": [
+                { "itemIdentifier": "s3://uploads/reports/q1 2024.csv", "statusCode": 204 },
+                { "itemIdentifier": "s3://uploads/missing.csv", "statusCode": 404 }
+            ]
+        })
+        .to_string(),
+        result.expect_err("Expected an error").to_string()
+    );
+
+    // keys with dot segments are not sent, so they can't escape the route
+    let admin_endpoint = app_server.mock(|when, then| {
+        when.path("/admin/reset");
+        then.status(200);
+    });
+    let result = adapter
+        .call(invoke(json!({ "Records": [record("../../admin/reset")] })))
+        .await;
+    assert_eq!(
+        json!({ "results": [{ "itemIdentifier": "s3://uploads/../../admin/reset", "statusCode": null }] }).to_string(),
+        result.expect_err("Expected an error").to_string()
+    );
+
+    report_endpoint.assert_calls(2);
+    missing_endpoint.assert();
+    admin_endpoint.assert_calls(0);
+}
+
 #[tokio::test]
 async fn test_http_error_status_codes_non_matching() {
     // Start app server
@@ -1110,7 +1571,7 @@ async fn test_http_async_init_ready_at_init() {
     .expect("Failed to create adapter");
 
     // Perform init health check — app is already running so it should succeed
-    adapter.check_init_health().await;
+    adapter.check_init_health().await.unwrap();
 
     healthcheck.assert();
 
@@ -1143,7 +1604,7 @@ async fn test_http_tcp_readiness_check() {
     .expect("Failed to create adapter");
 
     // TCP readiness check should succeed since MockServer is listening
-    adapter.check_init_health().await;
+    adapter.check_init_health().await.unwrap();
 
     // Now verify the adapter can still forward requests
     let hello = app_server.mock(|when, then| {
@@ -1162,12 +1623,12 @@ async fn test_http_tcp_readiness_check() {
     assert_eq!("TCP Ready", body_to_string(response).await);
 }
 
-async fn body_to_string(res: Response<Incoming>) -> String {
+async fn body_to_string(res: Response<AdapterBody>) -> String {
     let body_bytes = res.collect().await.unwrap().to_bytes();
     String::from_utf8_lossy(&body_bytes).to_string()
 }
 
-async fn compressed_body_to_string(res: Response<CompressionBody<Incoming>>) -> String {
+async fn compressed_body_to_string(res: Response<CompressionBody<AdapterBody>>) -> String {
     let body_bytes = res.collect().await.unwrap().to_bytes();
     decode_reader(&body_bytes).unwrap()
 }
@@ -1373,3 +1834,482 @@ async fn test_no_tenant_id_header_when_absent() {
     endpoint.assert();
     assert_eq!(200, response.status());
 }
+
+/// Starts a minimal HTTP/1.1 server on a Unix domain socket that echoes the request line.
+fn start_unix_socket_server(name: &str) -> std::path::PathBuf {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let path = env::temp_dir().join(format!("lwa-{}-{}.sock", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path).expect("Failed to bind unix socket");
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 1024];
+                while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut chunk).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&buf);
+                let request_line = head.lines().next().unwrap_or_default().to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    request_line.len(),
+                    request_line
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    path
+}
+
+#[tokio::test]
+async fn test_http_unix_socket_request() {
+    let socket_path = start_unix_socket_server("request");
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        unix_socket: Some(socket_path.to_string_lossy().to_string()),
+        readiness_check_path: "/healthcheck".to_string(),
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    adapter.check_init_health().await.unwrap();
+
+    let req = LambdaEventBuilder::new()
+        .with_path("/hello")
+        .with_query("foo", "bar")
+        .build();
+    let mut request = Request::from(req);
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    assert_eq!(200, response.status());
+    assert_eq!("GET /hello?foo=bar HTTP/1.1", body_to_string(response).await);
+
+    let _ = std::fs::remove_file(socket_path);
+}
+
+#[tokio::test]
+async fn test_http_h2c_request() {
+    let app_server = MockServer::start();
+    let hello = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .is_true(|req| req.version() == http::Version::HTTP_2);
+        then.status(200).body("Hello h2c");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        upstream_protocol: UpstreamProtocol::H2c,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let req = LambdaEventBuilder::new().with_path("/hello").build();
+    let mut request = Request::from(req);
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(http::Version::HTTP_2, response.version());
+    assert_eq!("Hello h2c", body_to_string(response).await);
+}
+
+#[tokio::test]
+async fn test_http_auto_protocol_detects_h2c() {
+    let app_server = MockServer::start();
+    let healthcheck = app_server.mock(|when, then| {
+        when.method(GET).path("/healthcheck");
+        then.status(200).body("OK");
+    });
+    let hello = app_server.mock(|when, then| {
+        when.method(GET).path("/hello");
+        then.status(200).body("Hello");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        upstream_protocol: UpstreamProtocol::Auto,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    adapter.check_init_health().await.unwrap();
+    healthcheck.assert();
+
+    let req = LambdaEventBuilder::new().with_path("/hello").build();
+    let mut request = Request::from(req);
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(http::Version::HTTP_2, response.version());
+}
+
+#[tokio::test]
+async fn test_http_auto_protocol_falls_back_to_http1() {
+    // The unix socket test server only speaks HTTP/1.1
+    let socket_path = start_unix_socket_server("auto-protocol");
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        unix_socket: Some(socket_path.to_string_lossy().to_string()),
+        readiness_check_path: "/healthcheck".to_string(),
+        upstream_protocol: UpstreamProtocol::Auto,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    adapter.check_init_health().await.unwrap();
+
+    let req = LambdaEventBuilder::new().with_path("/hello").build();
+    let mut request = Request::from(req);
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    assert_eq!(http::Version::HTTP_11, response.version());
+    assert_eq!("GET /hello HTTP/1.1", body_to_string(response).await);
+
+    let _ = std::fs::remove_file(socket_path);
+}
+
+fn add_lambda_context_with_deadline(request: &mut Request<Body>, deadline: std::time::SystemTime) {
+    let deadline_ms = deadline
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("deadline before unix epoch")
+        .as_millis();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("lambda-runtime-aws-request-id", "my_id".parse().unwrap());
+    headers.insert("lambda-runtime-deadline-ms", deadline_ms.to_string().parse().unwrap());
+    headers.insert("lambda-runtime-client-context", "{}".parse().unwrap());
+
+    let conf = Config {
+        function_name: "test_function".into(),
+        memory: 128,
+        version: "latest".into(),
+        log_stream: "/aws/lambda/test_function".into(),
+        log_group: "2023/09/15/[$LATEST]ab831cef03e94457a94b6efcbe22406a".into(),
+    };
+
+    let context = Context::new("my_id", Arc::new(conf), &headers).expect("Couldn't convert HeaderMap to Context");
+    request.extensions_mut().insert(context);
+}
+
+#[tokio::test]
+async fn test_http_request_timeout_returns_504() {
+    let app_server = MockServer::start();
+    let slow = app_server.mock(|when, then| {
+        when.method(GET).path("/slow");
+        then.status(200)
+            .delay(std::time::Duration::from_secs(2))
+            .body("too late");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        request_timeout_margin_ms: Some(100),
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let req = LambdaEventBuilder::new().with_path("/slow").build();
+    let mut request = Request::from(req);
+    let deadline = std::time::SystemTime::now() + std::time::Duration::from_millis(400);
+    add_lambda_context_with_deadline(&mut request, deadline);
+
+    let started = std::time::Instant::now();
+    let response = adapter.call(request).await.expect("Request failed");
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    assert_eq!(504, response.status());
+    assert_eq!(r#"{"message":"Gateway Timeout"}"#, body_to_string(response).await);
+    slow.assert();
+}
+
+#[tokio::test]
+async fn test_http_request_timeout_returns_error() {
+    let app_server = MockServer::start();
+    app_server.mock(|when, then| {
+        when.method(GET).path("/slow");
+        then.status(200)
+            .delay(std::time::Duration::from_secs(2))
+            .body("too late");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        request_timeout_margin_ms: Some(100),
+        request_timeout_action: RequestTimeoutAction::Error,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let req = LambdaEventBuilder::new().with_path("/slow").build();
+    let mut request = Request::from(req);
+    let deadline = std::time::SystemTime::now() + std::time::Duration::from_millis(400);
+    add_lambda_context_with_deadline(&mut request, deadline);
+
+    let result = adapter.call(request).await;
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("timed out"));
+}
+
+#[tokio::test]
+async fn test_http_request_within_timeout() {
+    let app_server = MockServer::start();
+    let hello = app_server.mock(|when, then| {
+        when.method(GET).path("/hello");
+        then.status(200).body("Hello World");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        request_timeout_margin_ms: Some(100),
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let req = LambdaEventBuilder::new().with_path("/hello").build();
+    let mut request = Request::from(req);
+    let deadline = std::time::SystemTime::now() + std::time::Duration::from_secs(10);
+    add_lambda_context_with_deadline(&mut request, deadline);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(200, response.status());
+    assert_eq!("Hello World", body_to_string(response).await);
+}
+
+#[tokio::test]
+async fn test_http_deadline_headers() {
+    let app_server = MockServer::start();
+    let deadline = std::time::SystemTime::now() + std::time::Duration::from_secs(30);
+    let deadline_ms = deadline
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis()
+        .to_string();
+
+    let hello = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .header("x-amzn-deadline-ms", deadline_ms.as_str())
+            .is_true(|req| {
+                req.headers()
+                    .get("x-amzn-remaining-time-ms")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .is_some_and(|ms| ms > 25_000 && ms <= 30_000)
+            });
+        then.status(200).body("OK");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let req = LambdaEventBuilder::new().with_path("/hello").build();
+    let mut request = Request::from(req);
+    add_lambda_context_with_deadline(&mut request, deadline);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(200, response.status());
+}
+
+#[tokio::test]
+async fn test_http_remaining_time_zero_after_deadline() {
+    let app_server = MockServer::start();
+    let hello = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .header("x-amzn-deadline-ms", "123")
+            .header("x-amzn-remaining-time-ms", "0");
+        then.status(200).body("OK");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    // add_lambda_context_to_request uses a deadline far in the past
+    let req = LambdaEventBuilder::new().with_path("/hello").build();
+    let mut request = Request::from(req);
+    add_lambda_context_to_request(&mut request);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(200, response.status());
+}
+
+fn add_lambda_context_with_trace_id(request: &mut Request<Body>, xray_trace_id: &str) {
+    let mut headers = HeaderMap::new();
+    headers.insert("lambda-runtime-aws-request-id", "my_id".parse().unwrap());
+    headers.insert("lambda-runtime-deadline-ms", "123".parse().unwrap());
+    headers.insert("lambda-runtime-client-context", "{}".parse().unwrap());
+    headers.insert("lambda-runtime-trace-id", xray_trace_id.parse().unwrap());
+
+    let conf = Config {
+        function_name: "test_function".into(),
+        memory: 128,
+        version: "latest".into(),
+        log_stream: "/aws/lambda/test_function".into(),
+        log_group: "2023/09/15/[$LATEST]ab831cef03e94457a94b6efcbe22406a".into(),
+    };
+
+    let context = Context::new("my_id", Arc::new(conf), &headers).expect("Couldn't convert HeaderMap to Context");
+    request.extensions_mut().insert(context);
+}
+
+#[tokio::test]
+async fn test_http_trace_headers() {
+    let xray_trace_id = "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1";
+    let app_server = MockServer::start();
+    let hello = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .header("x-amzn-trace-id", xray_trace_id)
+            .header("traceparent", "00-5759e988bd862e3fe1be46a994272793-53995c3f42cd8ad8-01")
+            .header_missing("tracestate");
+        then.status(200).body("OK");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        trace_propagation: TracePropagation::All,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    // the client's trace headers are replaced by the Lambda trace, its tracestate is dropped
+    let req = LambdaEventBuilder::new()
+        .with_path("/hello")
+        .with_header("x-amzn-trace-id", "Root=1-00000000-000000000000000000000001")
+        .with_header("traceparent", "00-00000000000000000000000000000001-0000000000000001-01")
+        .with_header("tracestate", "vendor=value")
+        .build();
+    let mut request = Request::from(req);
+    add_lambda_context_with_trace_id(&mut request, xray_trace_id);
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(200, response.status());
+}
+
+#[tokio::test]
+async fn test_http_trace_propagation_xray_keeps_tracestate() {
+    let app_server = MockServer::start();
+    let hello = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .header("traceparent", "00-00000000000000000000000000000001-0000000000000001-01")
+            .header("tracestate", "vendor=value");
+        then.status(200).body("OK");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        trace_propagation: TracePropagation::Xray,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    // traceparent is not replaced, so the client's tracestate still applies to it
+    let req = LambdaEventBuilder::new()
+        .with_path("/hello")
+        .with_header("traceparent", "00-00000000000000000000000000000001-0000000000000001-01")
+        .with_header("tracestate", "vendor=value")
+        .build();
+    let mut request = Request::from(req);
+    add_lambda_context_with_trace_id(
+        &mut request,
+        "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+    );
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(200, response.status());
+}
+
+#[tokio::test]
+async fn test_http_trace_propagation_none() {
+    let app_server = MockServer::start();
+    let hello = app_server.mock(|when, then| {
+        when.method(GET)
+            .path("/hello")
+            .header_missing("x-amzn-trace-id")
+            .header_missing("traceparent");
+        then.status(200).body("OK");
+    });
+
+    let mut adapter = Adapter::new(&AdapterOptions {
+        host: app_server.host(),
+        port: app_server.port().to_string(),
+        readiness_check_port: app_server.port().to_string(),
+        readiness_check_path: "/healthcheck".to_string(),
+        trace_propagation: TracePropagation::None,
+        ..Default::default()
+    })
+    .expect("Failed to create adapter");
+
+    let req = LambdaEventBuilder::new().with_path("/hello").build();
+    let mut request = Request::from(req);
+    add_lambda_context_with_trace_id(
+        &mut request,
+        "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+    );
+
+    let response = adapter.call(request).await.expect("Request failed");
+
+    hello.assert();
+    assert_eq!(200, response.status());
+}